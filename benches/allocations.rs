@@ -0,0 +1,127 @@
+//! Heap allocations made while extracting a module, counted with a wrapping global allocator.
+//!
+//! Run with `cargo bench --bench allocations`. Each case prints the number of allocations and the bytes they
+//! requested, so the effect of reserving the tables up front and of recycling buffers through a `BufferPool`
+//! shows up directly.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::pool::BufferPool;
+use h5_dumper::H5Module;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Runs `f` and prints the allocations it made under `name`.
+fn count(name: &str, f: impl FnOnce()) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    f();
+    println!(
+        "{name:<32} {:>8} allocations {:>12} bytes",
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes
+    );
+}
+
+/// Payload that compresses roughly like tag data, neither trivially nor not at all.
+fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| ((i / 7) ^ (i / 251)) as u8).collect()
+}
+
+/// Parses the tables of a module with tens of thousands of entries.
+fn read_tables() {
+    let names: Vec<_> = (0..20_000)
+        .map(|i| format!("objects/tag_{i}.model"))
+        .collect();
+    let files: Vec<_> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| FixtureFile {
+            name,
+            group: "mode",
+            payload: &[0],
+            storage: match i % 2 {
+                0 => Storage::Uncompressed,
+                _ => Storage::Blocks(1),
+            },
+        })
+        .collect();
+    let mut reader = Cursor::new(common::build_module(27, &files));
+    count("read 20000 entry tables", || {
+        black_box(H5Module::from_reader(&mut reader).unwrap());
+    });
+}
+
+/// Extracts every tag of a module, with and without recycling buffers.
+fn extract_tags() {
+    let payloads: Vec<_> = (0..256).map(|i| payload(16 << 10 << (i % 4))).collect();
+    let names: Vec<_> = (0..payloads.len())
+        .map(|i| format!("objects/tag_{i}.model"))
+        .collect();
+    let files: Vec<_> = payloads
+        .iter()
+        .zip(&names)
+        .enumerate()
+        .map(|(i, (payload, name))| FixtureFile {
+            name,
+            group: "mode",
+            payload,
+            storage: match i % 2 {
+                0 => Storage::Compressed,
+                _ => Storage::Blocks(0x4000),
+            },
+        })
+        .collect();
+    let mut reader = Cursor::new(common::build_module(27, &files));
+    let module = H5Module::from_reader(&mut reader).unwrap();
+
+    count("extract 256 tags, no pool", || {
+        for index in 0..module.files.len() {
+            black_box(module.extract_tag(index as u32, &mut reader).unwrap());
+        }
+    });
+    count("extract 256 tags, shared pool", || {
+        let mut pool = BufferPool::default();
+        for index in 0..module.files.len() {
+            let data = module
+                .extract_tag_with_pool(index as u32, &mut reader, &mut pool)
+                .unwrap();
+            pool.give(black_box(data));
+        }
+    });
+}
+
+fn main() {
+    read_tables();
+    extract_tags();
+}
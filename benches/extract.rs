@@ -0,0 +1,92 @@
+//! Extraction throughput, reported in bytes of decompressed output per second.
+//!
+//! `extract 512 blocks` covers a single tag split into many blocks, the path `read_tag` spends most of its time
+//! in. `read and extract module` parses a module with many tags of every storage kind and extracts all of them.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::{FixtureFile, Storage};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use h5_dumper::pool::BufferPool;
+use h5_dumper::H5Module;
+use std::hint::black_box;
+use std::io::Cursor;
+
+/// Payload that compresses roughly like tag data, neither trivially nor not at all.
+fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| ((i / 7) ^ (i / 251)) as u8).collect()
+}
+
+fn extract_blocks(c: &mut Criterion) {
+    let payload = payload(8 << 20);
+    let bytes = common::build_module(
+        27,
+        &[FixtureFile {
+            name: "objects/large.model",
+            group: "mode",
+            payload: &payload,
+            storage: Storage::Blocks(0x4000),
+        }],
+    );
+    let mut reader = Cursor::new(bytes);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+    let mut pool = BufferPool::default();
+
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Bytes(payload.len() as u64));
+    group.bench_function("extract 512 blocks", |b| {
+        b.iter(|| {
+            let data = module
+                .extract_tag_with_pool(0, &mut reader, &mut pool)
+                .unwrap();
+            pool.give(black_box(data));
+        })
+    });
+    group.finish();
+}
+
+fn read_and_extract_module(c: &mut Criterion) {
+    let payloads: Vec<_> = (0..256).map(|i| payload(16 << 10 << (i % 4))).collect();
+    let names: Vec<_> = (0..payloads.len())
+        .map(|i| format!("objects/tag_{i}.model"))
+        .collect();
+    let files: Vec<_> = payloads
+        .iter()
+        .zip(&names)
+        .enumerate()
+        .map(|(i, (payload, name))| FixtureFile {
+            name,
+            group: "mode",
+            payload,
+            storage: match i % 3 {
+                0 => Storage::Compressed,
+                1 => Storage::Blocks(0x4000),
+                _ => Storage::Raw,
+            },
+        })
+        .collect();
+    let total: usize = payloads.iter().map(Vec::len).sum();
+    let mut reader = Cursor::new(common::build_module(27, &files));
+    let mut pool = BufferPool::default();
+
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Bytes(total as u64));
+    group.sample_size(20);
+    group.bench_function("read and extract module", |b| {
+        b.iter(|| {
+            reader.set_position(0);
+            let module = H5Module::from_reader(&mut reader).unwrap();
+            for index in 0..module.files.len() {
+                let data = module
+                    .extract_tag_with_pool(index as u32, &mut reader, &mut pool)
+                    .unwrap();
+                pool.give(black_box(data));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, extract_blocks, read_and_extract_module);
+criterion_main!(benches);
@@ -0,0 +1,80 @@
+//! On-disk cache of parsed module tables, so repeated runs over the same modules skip parsing them.
+//!
+//! Every module is stored as the JSON of its `H5Module`, without any tag data, in a file named after a hash of
+//! the module's path. A cached module is only used while the module's modification time, header checksum, id
+//! and file size still match it. Campaign modules have no header checksum, so for them the modification time is
+//! what catches a module rewritten in place with the same size.
+
+use crate::loader::{H5Module, ModuleHeader};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A cached module together with the modification time of its file when it was cached.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<M> {
+    modified: Option<SystemTime>,
+    module: M,
+}
+
+#[derive(Debug)]
+pub struct IndexCache {
+    directory: PathBuf,
+}
+
+impl IndexCache {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        IndexCache {
+            directory: directory.into(),
+        }
+    }
+
+    /// Returns the path the tables of the module at `module_path` are cached at.
+    pub fn entry_path(&self, module_path: &Path) -> PathBuf {
+        let key = xxhash_rust::xxh3::xxh3_64(module_path.to_string_lossy().as_bytes());
+        let stem = module_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        self.directory.join(format!("{stem}-{key:016x}.json"))
+    }
+
+    /// Returns the cached tables of the module at `module_path`, or `None` when nothing is cached for it, the
+    /// module changed since it was cached or the cache entry cannot be parsed.
+    pub fn load(&self, module_path: &Path) -> Result<Option<H5Module>> {
+        let file = match File::open(self.entry_path(module_path)) {
+            Ok(file) => file,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let Ok(entry) = serde_json::from_reader::<_, CacheEntry<H5Module>>(BufReader::new(file))
+        else {
+            return Ok(None);
+        };
+        let cached = entry.module;
+        let header = ModuleHeader::open_header_only(module_path)?;
+        let metadata = std::fs::metadata(module_path)?;
+        let unchanged = entry.modified.is_some()
+            && entry.modified == metadata.modified().ok()
+            && cached.header.checksum == header.checksum
+            && cached.header.module_id == header.module_id
+            && cached.file_len == Some(metadata.len());
+        Ok(unchanged.then_some(cached))
+    }
+
+    /// Caches the tables of `module`, which was parsed from `module_path`.
+    pub fn store(&self, module_path: &Path, module: &H5Module) -> Result<()> {
+        std::fs::create_dir_all(&self.directory)?;
+        let mut writer = BufWriter::new(File::create(self.entry_path(module_path))?);
+        let entry = CacheEntry {
+            modified: std::fs::metadata(module_path)?.modified().ok(),
+            module,
+        };
+        serde_json::to_writer(&mut writer, &entry)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+//! Checksum probing for reverse-engineering the block checksum algorithm.
+//!
+//! Forge modules store a 64-bit checksum per block but the algorithm that produces it is unknown. This module
+//! runs a set of candidate algorithms over each block's compressed and uncompressed bytes and counts how often
+//! each candidate reproduces the stored value. New candidates only need to implement `ChecksumCandidate` and
+//! be listed in `all_candidates`.
+
+use crate::decompress::{BlockDecompressor, ZlibDecompressor};
+use crate::loader::{FileFlags, H5Module};
+use anyhow::{anyhow, bail, Result};
+use std::io::{BufRead, Seek, SeekFrom};
+
+/// A checksum algorithm that may have produced the stored block checksums.
+pub trait ChecksumCandidate: Send + Sync {
+    /// Name used on the command line and in reports.
+    fn name(&self) -> &'static str;
+    /// Computes the checksum of `data`, zero-extended to 64 bits for narrower algorithms.
+    fn compute(&self, data: &[u8]) -> u64;
+}
+
+pub struct Crc32;
+
+impl ChecksumCandidate for Crc32 {
+    fn name(&self) -> &'static str {
+        "crc32"
+    }
+
+    fn compute(&self, data: &[u8]) -> u64 {
+        crc32fast::hash(data) as u64
+    }
+}
+
+pub struct Xxh32;
+
+impl ChecksumCandidate for Xxh32 {
+    fn name(&self) -> &'static str {
+        "xxh32"
+    }
+
+    fn compute(&self, data: &[u8]) -> u64 {
+        xxhash_rust::xxh32::xxh32(data, 0) as u64
+    }
+}
+
+pub struct Xxh64;
+
+impl ChecksumCandidate for Xxh64 {
+    fn name(&self) -> &'static str {
+        "xxh64"
+    }
+
+    fn compute(&self, data: &[u8]) -> u64 {
+        xxhash_rust::xxh64::xxh64(data, 0)
+    }
+}
+
+pub struct Fnv1a64;
+
+impl ChecksumCandidate for Fnv1a64 {
+    fn name(&self) -> &'static str {
+        "fnv1a64"
+    }
+
+    fn compute(&self, data: &[u8]) -> u64 {
+        data.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+}
+
+pub struct Sum32;
+
+impl ChecksumCandidate for Sum32 {
+    fn name(&self) -> &'static str {
+        "sum32"
+    }
+
+    fn compute(&self, data: &[u8]) -> u64 {
+        data.iter()
+            .fold(0u32, |sum, &byte| sum.wrapping_add(byte as u32)) as u64
+    }
+}
+
+pub struct Sum64;
+
+impl ChecksumCandidate for Sum64 {
+    fn name(&self) -> &'static str {
+        "sum64"
+    }
+
+    fn compute(&self, data: &[u8]) -> u64 {
+        data.iter()
+            .fold(0u64, |sum, &byte| sum.wrapping_add(byte as u64))
+    }
+}
+
+/// Returns every known candidate.
+pub fn all_candidates() -> Vec<Box<dyn ChecksumCandidate>> {
+    vec![
+        Box::new(Crc32),
+        Box::new(Xxh32),
+        Box::new(Xxh64),
+        Box::new(Fnv1a64),
+        Box::new(Sum32),
+        Box::new(Sum64),
+    ]
+}
+
+/// Resolves a single candidate name.
+pub fn candidate_by_name(name: &str) -> Result<Box<dyn ChecksumCandidate>> {
+    all_candidates()
+        .into_iter()
+        .find(|candidate| candidate.name() == name)
+        .ok_or_else(|| {
+            let known: Vec<_> = all_candidates().iter().map(|c| c.name()).collect();
+            anyhow::anyhow!(
+                "Unknown checksum algorithm '{name}', expected one of: {}",
+                known.join(", ")
+            )
+        })
+}
+
+/// Resolves candidate names, where `all` expands to every known candidate.
+pub fn candidates_by_name(names: &[String]) -> Result<Vec<Box<dyn ChecksumCandidate>>> {
+    if names.iter().any(|name| name == "all") {
+        return Ok(all_candidates());
+    }
+    names.iter().map(|name| candidate_by_name(name)).collect()
+}
+
+/// Match counts of a single candidate.
+pub struct ProbeResult {
+    pub name: &'static str,
+    pub compressed_matches: usize,
+    pub uncompressed_matches: usize,
+}
+
+/// Outcome of `probe`.
+pub struct ProbeReport {
+    /// Number of blocks every candidate was run over.
+    pub probed: usize,
+    /// Number of blocks that could not be read or decompressed and were left out.
+    pub skipped: usize,
+    /// One result per candidate, in the order given.
+    pub results: Vec<ProbeResult>,
+}
+
+/// Runs each candidate over every block referenced by a file in `module`.
+///
+/// Blocks are read with the same size and range checks as `H5Module::extract_tag`. A block that fails them, or
+/// fails to read or decompress, is counted as skipped rather than ending the probe.
+pub fn probe<R: BufRead + Seek>(
+    module: &H5Module,
+    reader: &mut R,
+    candidates: &[Box<dyn ChecksumCandidate>],
+) -> Result<ProbeReport> {
+    let mut report = ProbeReport {
+        probed: 0,
+        skipped: 0,
+        results: candidates
+            .iter()
+            .map(|candidate| ProbeResult {
+                name: candidate.name(),
+                compressed_matches: 0,
+                uncompressed_matches: 0,
+            })
+            .collect(),
+    };
+
+    for (file_index, file) in module.files.iter().enumerate() {
+        if !file.flags.contains(FileFlags::HAS_BLOCKS) || file.first_block_index < 0 {
+            continue;
+        }
+        let Ok(blocks) = module.file_blocks(file_index) else {
+            continue;
+        };
+
+        for (block_index, block) in blocks.iter().enumerate() {
+            let Ok((stored, uncompressed)) = read_block(module, file_index, block_index, reader)
+            else {
+                report.skipped += 1;
+                continue;
+            };
+            for (candidate, result) in candidates.iter().zip(&mut report.results) {
+                if candidate.compute(&stored) == block.checksum {
+                    result.compressed_matches += 1;
+                }
+                if candidate.compute(&uncompressed) == block.checksum {
+                    result.uncompressed_matches += 1;
+                }
+            }
+            report.probed += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// Reads block `block_index` of file `file_index`, returning its stored and its uncompressed bytes.
+fn read_block<R: BufRead + Seek>(
+    module: &H5Module,
+    file_index: usize,
+    block_index: usize,
+    reader: &mut R,
+) -> Result<(Vec<u8>, Vec<u8>)> {
+    let file = &module.files[file_index];
+    module.check_size_limit(file)?;
+    let (block, offset) = module
+        .block_slice(file_index, block_index)
+        .ok_or_else(|| anyhow!("Block {block_index} of '{}' is out of range!", file.name))?;
+    if block.uncompressed_size > file.total_uncompressed_size {
+        bail!(
+            "Block {block_index} of '{}' is larger than the file!",
+            file.name
+        )
+    }
+    module.check_data_range(file, offset, block.stored_size() as u64)?;
+
+    let mut stored = vec![0u8; block.stored_size() as usize];
+    reader.seek(SeekFrom::Start(offset))?;
+    reader.read_exact(&mut stored)?;
+    if !block.compressed {
+        return Ok((stored.clone(), stored));
+    }
+    let mut uncompressed = vec![0u8; block.uncompressed_size as usize];
+    ZlibDecompressor.decompress(&stored, &mut uncompressed)?;
+    Ok((stored, uncompressed))
+}
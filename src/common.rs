@@ -6,12 +6,18 @@
 //!
 //! These functions are implemented as traits in generics. Requires `<BufReaderExt + Read>` to be satisfied.
 //!
+//! Also provides `CountingReader`, a wrapper that tracks the current byte position without needing `Seek`, and
+//! `RetryingReader`, a wrapper that retries reads failing with transient IO errors.
+//!
 
-use std::io::{self, BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::thread;
+use std::time::Duration;
 
 /// Extension trait for BufReader to add custom reading methods.
 pub trait BufReaderExt: BufRead {
     /// Reads a UTF-8 encoded C-style string from the reader until a null terminator (0x00) is encountered.
+    /// Invalid UTF-8 is replaced with U+FFFD, like `read_fixed_string` does.
     ///
     /// # Returns
     ///
@@ -19,21 +25,19 @@ pub trait BufReaderExt: BufRead {
     ///
     /// # Errors
     ///
-    /// This function will return an error if:
-    /// * There's an I/O error while reading from the reader.
-    /// * The read bytes are not valid UTF-8.
+    /// This function will return an error if there's an I/O error while reading from the reader.
     fn read_cstring(&mut self) -> io::Result<String> {
         let mut buffer = Vec::new();
         self.read_until(0, &mut buffer)?;
         if buffer.ends_with(&[0]) {
             buffer.pop(); // remove null terminator from buffer
         }
-        String::from_utf8(buffer).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
     }
 
-    /// Reads a fixed-length UTF-8 encoded string from the reader.
-
+    /// Reads a fixed-length UTF-8 encoded string from the reader
     /// and trims any null bytes found at the end of the string.
+    /// Invalid UTF-8 is replaced with U+FFFD.
     ///
     /// # Arguments
     ///
@@ -57,3 +61,131 @@ pub trait BufReaderExt: BufRead {
 }
 
 impl<R: Read> BufReaderExt for BufReader<R> {}
+
+impl<T: AsRef<[u8]>> BufReaderExt for Cursor<T> {}
+
+/// Reader wrapper that keeps track of how many bytes have been consumed.
+///
+/// The position starts at zero and advances on every read, so it stays accurate for readers that cannot
+/// report their position themselves. When the inner reader implements `Seek`, seeks are forwarded and the
+/// position is updated to the offset the inner reader reports.
+pub struct CountingReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R> CountingReader<R> {
+    pub fn new(inner: R) -> Self {
+        CountingReader { inner, position: 0 }
+    }
+
+    /// Returns the number of bytes consumed so far, adjusted by any seeks.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for CountingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.position += amt as u64;
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+impl<R: BufRead> BufReaderExt for CountingReader<R> {}
+
+/// How often and how patiently `RetryingReader` retries a failed read.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Number of retries after the first failed attempt.
+    pub retries: u32,
+    /// Delay before the first retry, doubled for every following one.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            retries: 0,
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns whether an error may go away when the operation is retried.
+    ///
+    /// End-of-file and invalid data are permanent, retrying them would only hide corruption.
+    pub fn is_transient(error: &io::Error) -> bool {
+        matches!(
+            error.kind(),
+            io::ErrorKind::Interrupted
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::WouldBlock
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::NotConnected
+                | io::ErrorKind::BrokenPipe
+        )
+    }
+}
+
+/// Reader wrapper that retries reads failing with a transient error according to a `RetryPolicy`.
+///
+/// A failed `read` consumes no bytes, so retrying it is safe. Seeks are forwarded without retries.
+pub struct RetryingReader<R> {
+    inner: R,
+    policy: RetryPolicy,
+}
+
+impl<R> RetryingReader<R> {
+    pub fn new(inner: R, policy: RetryPolicy) -> Self {
+        RetryingReader { inner, policy }
+    }
+}
+
+impl<R: Read> Read for RetryingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut attempt = 0;
+        loop {
+            match self.inner.read(buf) {
+                Err(error)
+                    if attempt < self.policy.retries && RetryPolicy::is_transient(&error) =>
+                {
+                    thread::sleep(self.policy.backoff.saturating_mul(1 << attempt.min(16)));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<R: Seek> Seek for RetryingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
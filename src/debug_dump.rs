@@ -0,0 +1,139 @@
+//! Hexdumps of entries that failed to extract, meant to be attached to bug reports.
+//!
+//! Each dump holds the entry's metadata, its block table and the first and last bytes of its compressed
+//! region. Both the byte windows and the block table are capped so dumps stay small.
+
+use crate::loader::H5Module;
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Number of bytes dumped from each end of the compressed region.
+const DUMP_BYTES: u64 = 256;
+/// Maximum number of block table rows written per file.
+const MAX_BLOCK_ROWS: usize = 64;
+
+/// Writes a failure report for file `index` of `module` into `dir` and returns its path.
+pub fn write_failure_dump<R: Read + Seek>(
+    dir: &Path,
+    module_path: &Path,
+    module: &H5Module,
+    index: usize,
+    reader: &mut R,
+    error: &anyhow::Error,
+) -> Result<PathBuf> {
+    let file = &module.files[index];
+    let region_start = module.data_offset + file.data_offset;
+    let region_size = file.total_compressed_size as u64;
+
+    let mut report = String::new();
+    writeln!(report, "module: {}", module_path.display())?;
+    writeln!(report, "file: {} ({})", index, file.name)?;
+    writeln!(report, "error: {error:#}")?;
+    writeln!(report, "group_tag: {}", file.group_tag)?;
+    writeln!(report, "flags: {:?}", file.flags)?;
+    writeln!(report, "region: {region_start:#x} + {region_size:#x}")?;
+    writeln!(
+        report,
+        "sizes: compressed {} uncompressed {} (header {} tag {} resource {})",
+        file.total_compressed_size,
+        file.total_uncompressed_size,
+        file.uncompressed_header_size,
+        file.uncompressed_tag_size,
+        file.uncompressed_resource_size
+    )?;
+    writeln!(
+        report,
+        "blocks: first {} count {} (module has {})",
+        file.first_block_index,
+        file.block_count,
+        module.blocks.len()
+    )?;
+
+    if file.first_block_index >= 0 {
+        let blocks = module
+            .blocks
+            .iter()
+            .enumerate()
+            .skip(file.first_block_index as usize)
+            .take((file.block_count as usize).min(MAX_BLOCK_ROWS));
+        for (block_index, block) in blocks {
+            writeln!(
+                report,
+                "  [{block_index}] compressed {:#x}+{:#x} uncompressed {:#x}+{:#x} compressed_flag {} checksum {:#018x}",
+                block.compressed_offset,
+                block.compressed_size,
+                block.uncompressed_offset,
+                block.uncompressed_size,
+                block.compressed,
+                block.checksum
+            )?;
+        }
+        if file.block_count as usize > MAX_BLOCK_ROWS {
+            writeln!(
+                report,
+                "  ... {} more",
+                file.block_count as usize - MAX_BLOCK_ROWS
+            )?;
+        }
+    }
+
+    let head_size = region_size.min(DUMP_BYTES);
+    writeln!(report, "first {head_size} bytes:")?;
+    hexdump(
+        &mut report,
+        region_start,
+        &read_window(reader, region_start, head_size)?,
+    )?;
+    if region_size > DUMP_BYTES {
+        let tail_size = (region_size - DUMP_BYTES).min(DUMP_BYTES);
+        let tail_start = region_start + region_size - tail_size;
+        writeln!(report, "last {tail_size} bytes:")?;
+        hexdump(
+            &mut report,
+            tail_start,
+            &read_window(reader, tail_start, tail_size)?,
+        )?;
+    }
+
+    fs::create_dir_all(dir)?;
+    let stem = module_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let dump_path = dir.join(format!("{stem}_{index}.txt"));
+    fs::write(&dump_path, report)?;
+    Ok(dump_path)
+}
+
+/// Reads up to `size` bytes at `offset`, stopping early at end-of-file.
+fn read_window<R: Read + Seek>(reader: &mut R, offset: u64, size: u64) -> Result<Vec<u8>> {
+    let mut buffer = Vec::with_capacity(size as usize);
+    reader.seek(SeekFrom::Start(offset))?;
+    reader.take(size).read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+fn hexdump(out: &mut String, base: u64, bytes: &[u8]) -> std::fmt::Result {
+    for (row, chunk) in bytes.chunks(16).enumerate() {
+        write!(out, "  {:08x} ", base + row as u64 * 16)?;
+        for byte in chunk {
+            write!(out, " {byte:02x}")?;
+        }
+        for _ in chunk.len()..16 {
+            out.push_str("   ");
+        }
+        out.push_str("  ");
+        for &byte in chunk {
+            out.push(if byte.is_ascii_graphic() {
+                byte as char
+            } else {
+                '.'
+            });
+        }
+        out.push('\n');
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Codecs for the compressed streams of tags and blocks.
+//!
+//! Every known module uses zlib, `BlockDecompressor` lets other codecs be plugged into extraction through
+//! `H5Module::extract_tag_with_decompressor`.
+
+use crate::loader::ModuleError;
+use anyhow::{bail, Result};
+use flate2::bufread::ZlibDecoder;
+use flate2::{Decompress, FlushDecompress, Status};
+use std::cell::RefCell;
+use std::io::{self, BufRead, Read, Write};
+
+/// Decompresses one compressed stream, either a whole tag or a single block.
+pub trait BlockDecompressor: Send + Sync {
+    /// Decompresses `input` into `output`, failing with `ModuleError::DecompressedSizeMismatch` if the stream
+    /// does not decode to exactly `output.len()` bytes.
+    fn decompress(&self, input: &[u8], output: &mut [u8]) -> Result<()>;
+
+    /// Decompresses the stream read from `input` into `output`, with the same size check as `decompress`.
+    ///
+    /// The default reads all of `input` into memory and calls `decompress`, codecs that can inflate
+    /// incrementally should override this to avoid holding the compressed bytes.
+    fn decompress_stream(&self, input: &mut dyn BufRead, output: &mut [u8]) -> Result<()> {
+        let mut buffer = Vec::new();
+        input.read_to_end(&mut buffer)?;
+        self.decompress(&buffer, output)
+    }
+
+    /// Decompresses the stream read from `input` and writes it to `output`, failing with
+    /// `ModuleError::DecompressedSizeMismatch` if the stream does not decode to exactly `size` bytes. Bytes past
+    /// `size` are not written.
+    ///
+    /// The default decompresses into a buffer of `size` bytes with `decompress_stream` and writes that, codecs
+    /// that can inflate incrementally should override this to avoid holding the decompressed bytes.
+    fn decompress_stream_to(
+        &self,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+        size: u64,
+    ) -> Result<()> {
+        let mut buffer = vec![0; size as usize];
+        self.decompress_stream(input, &mut buffer)?;
+        output.write_all(&buffer)?;
+        Ok(())
+    }
+}
+
+/// The zlib codec used by every known module.
+///
+/// Reuses one inflate state per thread instead of allocating one per stream.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ZlibDecompressor;
+
+impl BlockDecompressor for ZlibDecompressor {
+    fn decompress(&self, input: &[u8], output: &mut [u8]) -> Result<()> {
+        thread_local! {
+            static DECOMPRESSOR: RefCell<Decompress> = RefCell::new(Decompress::new(true));
+        }
+        let actual = DECOMPRESSOR.with_borrow_mut(|decompressor| {
+            decompressor.reset(true);
+            inflate_into(decompressor, input, output)
+        })?;
+        check_size(output.len() as u64, actual)
+    }
+
+    fn decompress_stream(&self, input: &mut dyn BufRead, output: &mut [u8]) -> Result<()> {
+        let mut decoder = ZlibDecoder::new(input);
+        let mut filled = 0;
+        while filled < output.len() {
+            match decoder.read(&mut output[filled..])? {
+                0 => break,
+                read => filled += read,
+            }
+        }
+        let extra = io::copy(&mut decoder, &mut io::sink())?;
+        check_size(output.len() as u64, filled as u64 + extra)
+    }
+
+    fn decompress_stream_to(
+        &self,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+        size: u64,
+    ) -> Result<()> {
+        let mut decoder = ZlibDecoder::new(input);
+        let written = io::copy(&mut decoder.by_ref().take(size), output)?;
+        let extra = io::copy(&mut decoder, &mut io::sink())?;
+        check_size(size, written + extra)
+    }
+}
+
+/// Inflates the zlib stream in `input` into `output` and returns the full decoded length of the stream, which
+/// may be smaller or larger than `output`. Bytes past the end of `output` are decoded and discarded.
+fn inflate_into(decompressor: &mut Decompress, input: &[u8], output: &mut [u8]) -> io::Result<u64> {
+    let invalid = |error| io::Error::new(io::ErrorKind::InvalidData, error);
+    let status = decompressor
+        .decompress(input, output, FlushDecompress::Finish)
+        .map_err(invalid)?;
+    if status == Status::StreamEnd || decompressor.total_out() < output.len() as u64 {
+        return Ok(decompressor.total_out());
+    }
+
+    let mut overflow = [0u8; 4096];
+    loop {
+        let consumed = decompressor.total_in() as usize;
+        let produced = decompressor.total_out();
+        let status = decompressor
+            .decompress(&input[consumed..], &mut overflow, FlushDecompress::Finish)
+            .map_err(invalid)?;
+        if status == Status::StreamEnd || decompressor.total_out() == produced {
+            return Ok(decompressor.total_out());
+        }
+    }
+}
+
+fn check_size(expected: u64, actual: u64) -> Result<()> {
+    if actual != expected {
+        bail!(ModuleError::DecompressedSizeMismatch { expected, actual })
+    }
+    Ok(())
+}
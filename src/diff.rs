@@ -0,0 +1,47 @@
+//! Comparison of the file tables of two modules, e.g. of two game builds.
+
+use crate::loader::{H5Module, ModuleFileEntry};
+
+/// How files of the old module are matched to files of the new one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum MatchKey {
+    /// Files with the same name.
+    Name,
+    /// Files with the same `global_tag_id`.
+    TagId,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// The file has no match in the old module.
+    Added,
+    /// The matching file of the old module has a different `asset_checksum` or uncompressed size.
+    Modified,
+}
+
+/// Returns the indices of the files of `new` that were added or modified since `old`, in table order.
+pub fn changed_entries(old: &H5Module, new: &H5Module, key: MatchKey) -> Vec<(usize, Change)> {
+    new.files
+        .iter()
+        .enumerate()
+        .filter_map(|(index, file)| {
+            let matching = match key {
+                MatchKey::Name => old.find_by_name(&file.name),
+                MatchKey::TagId => old.find_by_tag_id(file.global_tag_id),
+            };
+            match matching {
+                None => Some((index, Change::Added)),
+                Some(old_index) if differs(&old.files[old_index], file) => {
+                    Some((index, Change::Modified))
+                }
+                Some(_) => None,
+            }
+        })
+        .collect()
+}
+
+fn differs(old: &ModuleFileEntry, new: &ModuleFileEntry) -> bool {
+    old.asset_checksum != new.asset_checksum
+        || old.total_uncompressed_size != new.total_uncompressed_size
+}
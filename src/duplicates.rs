@@ -0,0 +1,156 @@
+//! Discovery and skipping of duplicated entries within and across modules.
+
+use crate::loader::H5Module;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io::{BufRead, Seek};
+use std::path::{Path, PathBuf};
+
+/// What makes two entries duplicates of each other.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum DuplicateKey {
+    /// Entries share an `asset_checksum`.
+    AssetChecksum,
+    /// Entries share an `asset_id` (entries without one are ignored).
+    AssetId,
+    /// Entries decompress to identical bytes (xxh3-128 of the data).
+    Content,
+}
+
+/// A single entry belonging to a duplicate group.
+#[derive(Debug)]
+pub struct Occurrence {
+    pub module: String,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct DuplicateGroup {
+    pub key: u128,
+    pub size: u64,
+    pub occurrences: Vec<Occurrence>,
+}
+
+impl DuplicateGroup {
+    /// Bytes that would be saved by storing this group's data once.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size * (self.occurrences.len() as u64 - 1)
+    }
+}
+
+/// Collects entries from any number of modules and groups them by `key`.
+pub struct DuplicateScanner {
+    key: DuplicateKey,
+    groups: HashMap<u128, DuplicateGroup>,
+}
+
+impl DuplicateScanner {
+    pub fn new(key: DuplicateKey) -> Self {
+        DuplicateScanner {
+            key,
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Adds every non-empty entry of `module`.
+    ///
+    /// Only `DuplicateKey::Content` reads tag data, the other keys work on metadata alone.
+    pub fn add_module<R: BufRead + Seek>(
+        &mut self,
+        module_name: &str,
+        module: &H5Module,
+        reader: &mut R,
+    ) -> Result<()> {
+        for index in 0..module.files.len() {
+            let file = &module.files[index];
+            if file.total_uncompressed_size == 0 {
+                continue;
+            }
+            let key = match self.key {
+                DuplicateKey::AssetChecksum => file.asset_checksum as u64 as u128,
+                DuplicateKey::AssetId if file.asset_id == -1 => continue,
+                DuplicateKey::AssetId => file.asset_id as u64 as u128,
+                DuplicateKey::Content => {
+                    xxhash_rust::xxh3::xxh3_128(&module.extract_tag(index as u32, reader)?)
+                }
+            };
+            let file = &module.files[index];
+            self.groups
+                .entry(key)
+                .or_insert_with(|| DuplicateGroup {
+                    key,
+                    size: file.total_uncompressed_size as u64,
+                    occurrences: Vec::new(),
+                })
+                .occurrences
+                .push(Occurrence {
+                    module: module_name.to_string(),
+                    name: file.name.clone(),
+                });
+        }
+        Ok(())
+    }
+
+    /// Returns the groups with more than one entry, sorted by wasted bytes in descending order.
+    pub fn into_duplicates(self) -> Vec<DuplicateGroup> {
+        let mut duplicates: Vec<_> = self
+            .groups
+            .into_values()
+            .filter(|group| group.occurrences.len() > 1)
+            .collect();
+        duplicates.sort_by(|a, b| {
+            b.wasted_bytes()
+                .cmp(&a.wasted_bytes())
+                .then(a.key.cmp(&b.key))
+        });
+        duplicates
+    }
+}
+
+/// A file that was not written because identical content had already been written elsewhere.
+#[derive(Debug)]
+pub struct DedupReference {
+    pub module: String,
+    pub name: String,
+    pub path: PathBuf,
+    pub original: PathBuf,
+}
+
+/// Remembers the content of every file written during a run so later identical files can be skipped.
+///
+/// Keeps one xxh3-128 hash and path per unique file for the whole run.
+#[derive(Debug, Default)]
+pub struct ContentDeduplicator {
+    written: HashMap<u128, PathBuf>,
+    pub references: Vec<DedupReference>,
+    pub bytes_saved: u64,
+}
+
+impl ContentDeduplicator {
+    /// Returns the path `data` was first written to, or `None` when the content has not been recorded yet.
+    pub fn original_of(&self, data: &[u8]) -> Option<&Path> {
+        self.written
+            .get(&xxhash_rust::xxh3::xxh3_128(data))
+            .map(PathBuf::as_path)
+    }
+
+    /// Records `path` as the first location of `data`, unless the content was already recorded.
+    pub fn record(&mut self, data: &[u8], path: &Path) {
+        self.written
+            .entry(xxhash_rust::xxh3::xxh3_128(data))
+            .or_insert_with(|| path.to_path_buf());
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "bytes_saved": self.bytes_saved,
+            "references": self.references.iter().map(|reference| serde_json::json!({
+                "module": reference.module,
+                "name": reference.name,
+                "path": reference.path,
+                "original": reference.original,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
@@ -0,0 +1,158 @@
+//! Tabular exports of parsed module structures.
+
+use crate::loader::{FileFlags, H5Module, ModuleHeader, ModuleVersion};
+use anyhow::Result;
+use std::io::Write;
+use std::path::Path;
+#[cfg(feature = "serde")]
+use {crate::loader::ModuleFileEntry, serde::Serialize};
+
+/// Column names written by `write_block_rows`.
+pub const BLOCK_CSV_HEADER: [&str; 9] = [
+    "module",
+    "index",
+    "checksum",
+    "compressed_offset",
+    "compressed_size",
+    "uncompressed_offset",
+    "uncompressed_size",
+    "compressed",
+    "referenced_by",
+];
+
+/// Writes one CSV row per block of `module`.
+///
+/// The last column lists the names of every file whose `first_block_index`/`block_count` range covers the
+/// block, separated by `;`.
+pub fn write_block_rows<W: Write>(
+    writer: &mut csv::Writer<W>,
+    module_name: &str,
+    module: &H5Module,
+) -> Result<()> {
+    let mut references: Vec<Vec<&str>> = vec![Vec::new(); module.blocks.len()];
+    for file in &module.files {
+        if file.first_block_index < 0 {
+            continue;
+        }
+        let first = file.first_block_index as usize;
+        let last = (first + file.block_count as usize).min(module.blocks.len());
+        for names in references.iter_mut().take(last).skip(first) {
+            names.push(&file.name);
+        }
+    }
+
+    for (index, (block, names)) in module.blocks.iter().zip(references).enumerate() {
+        writer.write_record([
+            module_name.to_string(),
+            index.to_string(),
+            format!("{:#018x}", block.checksum),
+            block.compressed_offset.to_string(),
+            block.compressed_size.to_string(),
+            block.uncompressed_offset.to_string(),
+            block.uncompressed_size.to_string(),
+            block.compressed.to_string(),
+            names.join(";"),
+        ])?;
+    }
+    Ok(())
+}
+
+/// Column names written by `write_file_rows`.
+pub const FILE_CSV_HEADER: [&str; 10] = [
+    "module",
+    "name",
+    "group_tag",
+    "global_tag_id",
+    "asset_id",
+    "asset_checksum",
+    "total_compressed_size",
+    "total_uncompressed_size",
+    "compressed",
+    "has_blocks",
+];
+
+/// Writes one CSV row per file of `module`.
+pub fn write_file_rows<W: Write>(
+    writer: &mut csv::Writer<W>,
+    module_name: &str,
+    module: &H5Module,
+) -> Result<()> {
+    for file in &module.files {
+        writer.write_record([
+            module_name.to_string(),
+            file.name.clone(),
+            file.group_tag.clone(),
+            file.global_tag_id.to_string(),
+            format!("{:016x}", file.asset_id as u64),
+            file.asset_checksum.to_string(),
+            file.total_compressed_size.to_string(),
+            file.total_uncompressed_size.to_string(),
+            file.flags.contains(FileFlags::COMPRESSED).to_string(),
+            file.flags.contains(FileFlags::HAS_BLOCKS).to_string(),
+        ])?;
+    }
+    Ok(())
+}
+
+/// Describes `header` as a JSON object, tagged with the module it was read from.
+pub fn header_json(module_path: &Path, header: &ModuleHeader) -> serde_json::Value {
+    serde_json::json!({
+        "module": module_path,
+        "magic": header.magic,
+        "version": header.version,
+        "title": ModuleVersion::try_from(header.version)
+            .ok()
+            .map(|version| version.title().to_string()),
+        "module_id": header.module_id,
+        "item_count": header.item_count,
+        "manifest_count": header.manifest_count,
+        "resource_index": header.resource_index,
+        "strings_size": header.strings_size,
+        "resource_count": header.resource_count,
+        "block_count": header.block_count,
+        "build_version": header.build_version,
+        "build": header.build_string(),
+        "checksum": header.checksum,
+    })
+}
+
+/// The output of the `manifest` command.
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ManifestDocument<'a> {
+    /// Always `manifest::SCHEMA_VERSION`.
+    pub schema_version: u32,
+    pub modules: Vec<ModuleManifest<'a>>,
+}
+
+/// The parsed header and file table of a module, as written by the `manifest` command.
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ModuleManifest<'a> {
+    pub module: &'a Path,
+    pub header: &'a ModuleHeader,
+    pub files: &'a [ModuleFileEntry],
+    pub manifest_indices: &'a [i32],
+    pub resource_indices: &'a [i32],
+}
+
+#[cfg(feature = "serde")]
+impl<'a> ModuleManifest<'a> {
+    pub fn new(module_path: &'a Path, module: &'a H5Module) -> Self {
+        ModuleManifest {
+            module: module_path,
+            header: &module.header,
+            files: &module.files,
+            manifest_indices: &module.manifest_indices,
+            resource_indices: &module.resource_indices,
+        }
+    }
+}
+
+/// Returns the JSON Schema of the `manifest` command's output, generated from `ManifestDocument`.
+#[cfg(feature = "schemars")]
+pub fn manifest_schema() -> schemars::Schema {
+    schemars::schema_for!(ManifestDocument<'static>)
+}
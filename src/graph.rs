@@ -0,0 +1,180 @@
+//! Parent and resource relationships between the entries of a module.
+//!
+//! Edges come from two places: an entry's `parent_file_index`, and the slice of `resource_indices` selected by
+//! its `first_resource_index`/`resource_count`. Edges whose target cannot be resolved inside the module are
+//! kept apart as external edges, as they most likely point into another module.
+
+use crate::loader::H5Module;
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// From an entry to the resource entry it lists.
+    Resource,
+    /// From an entry's `parent_file_index` to the entry.
+    Parent,
+}
+
+impl EdgeKind {
+    fn label(self) -> &'static str {
+        match self {
+            EdgeKind::Resource => "resource",
+            EdgeKind::Parent => "parent",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// An edge whose other end is not an entry of this module.
+#[derive(Debug)]
+pub struct ExternalEdge {
+    pub file: usize,
+    pub target: i64,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug)]
+pub struct TagGraph {
+    pub module: String,
+    /// One `(group_tag, name)` label per file, indexed like `H5Module::files`.
+    pub nodes: Vec<(String, String)>,
+    pub edges: Vec<Edge>,
+    pub external_edges: Vec<ExternalEdge>,
+}
+
+impl TagGraph {
+    pub fn new(module_name: &str, module: &H5Module) -> Self {
+        let file_count = module.files.len();
+        let mut graph = TagGraph {
+            module: module_name.to_string(),
+            nodes: module
+                .files
+                .iter()
+                .map(|file| (file.group_tag.clone(), file.name.clone()))
+                .collect(),
+            edges: Vec::new(),
+            external_edges: Vec::new(),
+        };
+
+        for (index, file) in module.files.iter().enumerate() {
+            if file.parent_file_index >= 0 {
+                graph.add_edge(
+                    file.parent_file_index as i64,
+                    index,
+                    EdgeKind::Parent,
+                    file_count,
+                );
+            }
+            if file.first_resource_index < 0 {
+                continue;
+            }
+            for slot in 0..file.resource_count as usize {
+                let target = module
+                    .resource_indices
+                    .get(file.first_resource_index as usize + slot)
+                    .map_or(-1, |&target| target as i64);
+                graph.add_edge(target, index, EdgeKind::Resource, file_count);
+            }
+        }
+        graph
+    }
+
+    /// Adds an edge between `file` and `other`, or an external edge when `other` is not in the module.
+    fn add_edge(&mut self, other: i64, file: usize, kind: EdgeKind, file_count: usize) {
+        if other < 0 || other as usize >= file_count {
+            self.external_edges.push(ExternalEdge {
+                file,
+                target: other,
+                kind,
+            });
+            return;
+        }
+        let (from, to) = match kind {
+            EdgeKind::Resource => (file, other as usize),
+            EdgeKind::Parent => (other as usize, file),
+        };
+        self.edges.push(Edge { from, to, kind });
+    }
+
+    /// Writes the graph as a DOT cluster named after the module.
+    pub fn write_dot(&self, out: &mut String, cluster: usize) -> std::fmt::Result {
+        let id = |index: usize| format!("m{cluster}_{index}");
+        writeln!(out, "  subgraph cluster_{cluster} {{")?;
+        writeln!(out, "    label=\"{}\";", escape_dot(&self.module))?;
+        for (index, (group, name)) in self.nodes.iter().enumerate() {
+            writeln!(
+                out,
+                "    {} [label=\"{}\\n{}\"];",
+                id(index),
+                escape_dot(group),
+                escape_dot(name)
+            )?;
+        }
+        for edge in &self.edges {
+            writeln!(
+                out,
+                "    {} -> {} [label=\"{}\"];",
+                id(edge.from),
+                id(edge.to),
+                edge.kind.label()
+            )?;
+        }
+        for (external_index, edge) in self.external_edges.iter().enumerate() {
+            let external = format!("m{cluster}_external_{external_index}");
+            writeln!(
+                out,
+                "    {external} [label=\"external {}\", shape=box, style=dashed];",
+                edge.target
+            )?;
+            writeln!(
+                out,
+                "    {} -> {external} [label=\"{}\", style=dashed];",
+                id(edge.file),
+                edge.kind.label()
+            )?;
+        }
+        writeln!(out, "  }}")
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "module": self.module,
+            "nodes": self.nodes.iter().enumerate().map(|(index, (group, name))| json!({
+                "index": index,
+                "group_tag": group,
+                "name": name,
+            })).collect::<Vec<_>>(),
+            "edges": self.edges.iter().map(|edge| json!({
+                "from": edge.from,
+                "to": edge.to,
+                "kind": edge.kind.label(),
+            })).collect::<Vec<_>>(),
+            "external_edges": self.external_edges.iter().map(|edge| json!({
+                "file": edge.file,
+                "target": edge.target,
+                "kind": edge.kind.label(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Renders the graphs of several modules into a single DOT document.
+pub fn to_dot(graphs: &[TagGraph]) -> Result<String, std::fmt::Error> {
+    let mut out = String::from("digraph tags {\n");
+    for (cluster, graph) in graphs.iter().enumerate() {
+        graph.write_dot(&mut out, cluster)?;
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
@@ -0,0 +1,166 @@
+//! Typed group tags and friendly names for the common Halo 5 tag groups.
+
+use std::fmt;
+
+/// The group of a tag, identified by its four-character code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GroupTag {
+    Biped,
+    Bitmap,
+    Character,
+    CollisionModel,
+    Creature,
+    DamageEffect,
+    Effect,
+    Equipment,
+    Material,
+    Model,
+    ModelAnimationGraph,
+    MultilingualUnicodeStringList,
+    PhysicsModel,
+    Projectile,
+    RenderModel,
+    Scenario,
+    ScenarioStructureBsp,
+    Scenery,
+    Sound,
+    SoundLooping,
+    Vehicle,
+    Weapon,
+    /// Any other group. Only built by the constructors below, so a known group is never `Custom`.
+    Custom(CustomGroup),
+}
+
+/// The four bytes, in reading order, of a group tag that is not one of the known groups.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CustomGroup([u8; 4]);
+
+impl CustomGroup {
+    /// Returns the four bytes of the group tag in reading order.
+    pub fn to_bytes(self) -> [u8; 4] {
+        self.0
+    }
+}
+
+/// Known groups with their group tag, as stored in `ModuleFileEntry::group_tag`, and tag group name.
+const GROUPS: [(GroupTag, &str, &str); 22] = [
+    (GroupTag::Biped, "bipd", "biped"),
+    (GroupTag::Bitmap, "bitm", "bitmap"),
+    (GroupTag::Character, "char", "character"),
+    (GroupTag::CollisionModel, "coll", "collision_model"),
+    (GroupTag::Creature, "crea", "creature"),
+    (GroupTag::DamageEffect, "jpt!", "damage_effect"),
+    (GroupTag::Effect, "effe", "effect"),
+    (GroupTag::Equipment, "eqip", "equipment"),
+    (GroupTag::Material, "mat ", "material"),
+    (GroupTag::Model, "hlmt", "model"),
+    (
+        GroupTag::ModelAnimationGraph,
+        "jmad",
+        "model_animation_graph",
+    ),
+    (
+        GroupTag::MultilingualUnicodeStringList,
+        "unic",
+        "multilingual_unicode_string_list",
+    ),
+    (GroupTag::PhysicsModel, "phmo", "physics_model"),
+    (GroupTag::Projectile, "proj", "projectile"),
+    (GroupTag::RenderModel, "mode", "render_model"),
+    (GroupTag::Scenario, "scnr", "scenario"),
+    (
+        GroupTag::ScenarioStructureBsp,
+        "sbsp",
+        "scenario_structure_bsp",
+    ),
+    (GroupTag::Scenery, "scen", "scenery"),
+    (GroupTag::Sound, "snd!", "sound"),
+    (GroupTag::SoundLooping, "lsnd", "sound_looping"),
+    (GroupTag::Vehicle, "vehi", "vehicle"),
+    (GroupTag::Weapon, "weap", "weapon"),
+];
+
+impl GroupTag {
+    /// Parses a group tag in reading order, e.g. `bitm`. Shorter tags are padded with spaces, so `mat` and
+    /// `mat ` are the same group; anything past four bytes is ignored.
+    pub fn from_fourcc(fourcc: &str) -> GroupTag {
+        let mut bytes = [b' '; 4];
+        for (byte, value) in bytes.iter_mut().zip(fourcc.bytes()) {
+            *byte = value;
+        }
+        GroupTag::from_bytes(bytes)
+    }
+
+    /// Converts the four bytes of a group tag in reading order.
+    pub fn from_bytes(bytes: [u8; 4]) -> GroupTag {
+        GROUPS
+            .iter()
+            .find(|(_, known, _)| known.as_bytes() == bytes)
+            .map_or(GroupTag::Custom(CustomGroup(bytes)), |(group, _, _)| *group)
+    }
+
+    /// Converts a group tag as stored in a module. Modules store group tags as little-endian integers, so the
+    /// characters appear reversed on disk.
+    pub fn from_le_bytes(mut bytes: [u8; 4]) -> GroupTag {
+        bytes.reverse();
+        GroupTag::from_bytes(bytes)
+    }
+
+    /// Returns the four bytes of the group tag in reading order.
+    pub fn to_bytes(self) -> [u8; 4] {
+        match self {
+            GroupTag::Custom(custom) => custom.0,
+            group => {
+                let (_, fourcc, _) = GROUPS.iter().find(|(known, _, _)| *known == group).unwrap();
+                fourcc.as_bytes().try_into().unwrap()
+            }
+        }
+    }
+
+    /// Returns the group tag in reading order, e.g. `mat ` for `Material`, or `None` for a custom tag that is
+    /// not valid UTF-8.
+    pub fn as_fourcc(&self) -> Option<&str> {
+        match self {
+            GroupTag::Custom(custom) => std::str::from_utf8(&custom.0).ok(),
+            group => GROUPS
+                .iter()
+                .find(|(known, _, _)| known == group)
+                .map(|(_, fourcc, _)| *fourcc),
+        }
+    }
+
+    /// Returns the tag group name, e.g. `material`, or `None` for a custom tag.
+    pub fn name(&self) -> Option<&'static str> {
+        GROUPS
+            .iter()
+            .find(|(known, _, _)| known == self)
+            .map(|(_, _, name)| *name)
+    }
+}
+
+impl From<&str> for GroupTag {
+    fn from(fourcc: &str) -> Self {
+        GroupTag::from_fourcc(fourcc)
+    }
+}
+
+/// Writes the group tag in reading order. Invalid UTF-8 is replaced with U+FFFD and NUL padding is trimmed.
+impl fmt::Display for GroupTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.to_bytes();
+        f.write_str(String::from_utf8_lossy(&bytes).trim_matches('\0'))
+    }
+}
+
+/// Returns the group tag of the tag group called `name` (case-insensitive), e.g. `mat ` for `material`.
+pub fn fourcc_for_name(name: &str) -> Option<&'static str> {
+    GROUPS
+        .iter()
+        .find(|(_, _, group)| group.eq_ignore_ascii_case(name))
+        .map(|(_, fourcc, _)| *fourcc)
+}
+
+/// Returns the name of the tag group with group tag `fourcc`. Trailing spaces are ignored.
+pub fn name_for_fourcc(fourcc: &str) -> Option<&'static str> {
+    GroupTag::from_fourcc(fourcc).name()
+}
@@ -0,0 +1,68 @@
+//! Content hashes of extracted tags, for comparing dumps without comparing their files byte by byte.
+
+use crc32fast::Hasher;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum HashAlgorithm {
+    Crc32,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Hashes `data`, big-endian for crc32.
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Crc32 => crc32fast::hash(data).to_be_bytes().to_vec(),
+            HashAlgorithm::Blake3 => blake3::hash(data).as_bytes().to_vec(),
+        }
+    }
+
+    /// Returns whether `digest` equals an entry's `asset_checksum`: the whole crc32, or the first eight bytes
+    /// of a blake3 hash read as little-endian.
+    pub fn matches_checksum(self, digest: &[u8], asset_checksum: i64) -> bool {
+        let value = match self {
+            HashAlgorithm::Crc32 => u32::from_be_bytes(digest[..4].try_into().unwrap()) as u64,
+            HashAlgorithm::Blake3 => u64::from_le_bytes(digest[..8].try_into().unwrap()),
+        };
+        value == asset_checksum as u64
+    }
+}
+
+/// Formats `digest` as lowercase hex.
+pub fn to_hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Writer that feeds every byte written through it into a CRC32 `Hasher`, so the checksum of an output is
+/// known once it is written without reading it back.
+///
+/// The hasher is borrowed so several writers can contribute to one checksum, e.g. the sections of a tag that
+/// are written to separate files.
+pub struct Crc32Writer<'a, W> {
+    inner: W,
+    hasher: &'a mut Hasher,
+}
+
+impl<'a, W: Write> Crc32Writer<'a, W> {
+    pub fn new(inner: W, hasher: &'a mut Hasher) -> Self {
+        Crc32Writer { inner, hasher }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for Crc32Writer<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,34 @@
+//! Reader for Halo 5 and Halo 5 Forge `.module` files.
+//!
+//! `H5Module::read` parses a module's header, file table, names and block table, after which individual tags
+//! can be decompressed one at a time with `H5Module::extract_tag`. The remaining modules build reports and
+//! exports on top of a parsed `H5Module`.
+
+#[cfg(feature = "serde")]
+pub mod cache;
+pub mod checksum;
+pub mod common;
+pub mod debug_dump;
+pub mod decompress;
+pub mod diff;
+pub mod duplicates;
+pub mod export;
+pub mod graph;
+pub mod groups;
+pub mod hash;
+pub mod loader;
+pub mod manifest;
+#[cfg(feature = "tokio")]
+pub mod nonblocking;
+#[cfg(feature = "serde")]
+pub mod pack;
+pub mod paths;
+pub mod pool;
+pub mod report;
+pub mod validate;
+
+pub use common::BufReaderExt;
+pub use loader::{
+    Endianness, EntryOrder, FileFlags, H5Module, ModuleBlock, ModuleError, ModuleFileEntry,
+    ModuleHeader, ModuleVersion, TagSection, Title,
+};
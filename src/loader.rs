@@ -1,24 +1,202 @@
-use crate::common::BufReaderExt;
+use crate::checksum::ChecksumCandidate;
+use crate::common::{BufReaderExt, CountingReader};
+use crate::decompress::{BlockDecompressor, ZlibDecompressor};
+use crate::groups::GroupTag;
+use crate::pool::BufferPool;
 use anyhow::{bail, Result};
 use bitflags::bitflags;
-use byteorder::{ReadBytesExt, LE};
-use flate2::bufread::ZlibDecoder;
-use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt, BE, LE};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ModuleError {
     #[error("Incorrect module version! Should be either 23 or 27. Found: {0}")]
     InvalidModuleVersion(u32),
+    #[error("This isn't a module! The file is only {size} byte(s) long, too small to hold the module magic.")]
+    NotAModule { size: usize },
     #[error("Module magic doesn't match! Expected 'mohd' found: {0}")]
     InvalidModuleMagic(String),
     #[error("Tag size is zero! This should not happen.")]
     EmptyTag,
-    #[error("Non-compressed single block tag found! This should not happen.")]
+    #[error("Non-compressed single block tag found whose compressed and uncompressed sizes differ! This should not happen.")]
     NonCompressedSingleTag,
+    #[error("Tag '{0}' is split into blocks but the module has no block table!")]
+    MissingBlockTable(String),
+    #[error("Name offset {offset} is outside the {size} byte string table! The module layout is likely not what the parser expects.")]
+    NameOffsetOutOfBounds { offset: u32, size: u32 },
+    #[error("Tag '{name}' has unknown flag bits {bits:#04x}!")]
+    UnknownFileFlags { name: String, bits: u8 },
+    #[error("Block {block} has compressed flag {value}, expected 0 or 1! The block table is likely misaligned.")]
+    InvalidCompressedFlag { block: usize, value: u32 },
+    #[error(
+        "Block {block} of tag '{file}' has checksum {actual:#018x}, expected {expected:#018x}!"
+    )]
+    ChecksumMismatch {
+        file: String,
+        block: usize,
+        expected: u64,
+        actual: u64,
+    },
+    #[error("Tag '{file}' references blocks {first}..{first}+{count} but the module only has {total} blocks!")]
+    BlockRangeOutOfBounds {
+        file: String,
+        first: i32,
+        count: u32,
+        total: usize,
+    },
+    #[error("{referrer} references index {index} of the {table} but it only has {total} entries!")]
+    ResourceIndexOutOfBounds {
+        referrer: String,
+        index: i64,
+        table: &'static str,
+        total: usize,
+    },
+    #[error("Tag '{file}' declares {size} uncompressed bytes, more than the limit of {limit}!")]
+    SizeLimitExceeded { file: String, size: u32, limit: u64 },
+    #[error("Data of tag '{file}' at offset {offset} with size {size} ends past the end of the {file_len} byte module! The module is likely truncated.")]
+    DataOutOfBounds {
+        file: String,
+        offset: u64,
+        size: u64,
+        file_len: u64,
+    },
+    #[error("Compressed stream decoded to {actual} bytes, expected {expected}! The size in the module metadata is likely wrong.")]
+    DecompressedSizeMismatch { expected: u64, actual: u64 },
+    #[error("The {section} section of tag '{file}' spans bytes {start}..{end} but the tag only has {size}!")]
+    SectionOutOfBounds {
+        file: String,
+        section: &'static str,
+        start: u64,
+        end: u64,
+        size: u32,
+    },
+    #[error("Stored data of block {block} of tag '{file}' {detail}!")]
+    BlockLayoutMismatch {
+        file: String,
+        block: usize,
+        detail: String,
+    },
+    #[error("Blocks of tag '{file}' do not tile its {size} bytes: {detail}!")]
+    SizeMismatch {
+        file: String,
+        size: u32,
+        detail: String,
+    },
+}
+
+/// Known module format versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModuleVersion {
+    H5Campaign = 23,
+    H5Forge = 27,
+}
+
+impl TryFrom<u32> for ModuleVersion {
+    type Error = ModuleError;
+
+    fn try_from(version: u32) -> Result<Self, Self::Error> {
+        match version {
+            23 => Ok(ModuleVersion::H5Campaign),
+            27 => Ok(ModuleVersion::H5Forge),
+            _ => Err(ModuleError::InvalidModuleVersion(version)),
+        }
+    }
+}
+
+impl ModuleVersion {
+    /// Every version the parser supports.
+    pub const ALL: [ModuleVersion; 2] = [ModuleVersion::H5Campaign, ModuleVersion::H5Forge];
+
+    /// Returns whether this version stores the header checksum and the per-block checksum and padding.
+    pub fn has_checksums(self) -> bool {
+        match self {
+            ModuleVersion::H5Campaign => false,
+            ModuleVersion::H5Forge => true,
+        }
+    }
+
+    /// Size of the module header in bytes.
+    pub fn header_size(self) -> usize {
+        if self.has_checksums() {
+            56
+        } else {
+            48
+        }
+    }
+
+    /// Size of a block table entry in bytes.
+    pub fn block_size(self) -> usize {
+        if self.has_checksums() {
+            32
+        } else {
+            20
+        }
+    }
+
+    /// The game this version belongs to.
+    pub fn title(self) -> Title {
+        match self {
+            ModuleVersion::H5Campaign => Title::Campaign,
+            ModuleVersion::H5Forge => Title::Forge,
+        }
+    }
+}
+
+/// The Halo title a module was built for, told apart by the module version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Title {
+    Campaign,
+    Forge,
+}
+
+impl fmt::Display for Title {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Title::Campaign => write!(f, "Halo 5 campaign"),
+            Title::Forge => write!(f, "Halo 5 Forge"),
+        }
+    }
+}
+
+/// Byte order of a module's header and tables. PC modules are little-endian, some console variants big-endian.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Detects the byte order from the four magic and version bytes at the start of a module.
+    ///
+    /// A byte-swapped `dhom` magic means big-endian, as does a version that is only known when read big-endian.
+    /// Anything else is taken to be little-endian.
+    pub fn detect(magic: &str, version: [u8; 4]) -> Endianness {
+        let known = |version| ModuleVersion::try_from(version).is_ok();
+        if magic == "dhom"
+            || (!known(u32::from_le_bytes(version)) && known(u32::from_be_bytes(version)))
+        {
+            Endianness::Big
+        } else {
+            Endianness::Little
+        }
+    }
 }
 
 #[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ModuleHeader {
     pub magic: String,
     pub version: u32,
@@ -31,35 +209,139 @@ pub struct ModuleHeader {
     pub block_count: u32,
     pub build_version: u64,
     pub checksum: u64,
+    /// Byte order the header and tables were read in, set by `read`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub endianness: Endianness,
 }
 
 impl ModuleHeader {
     pub fn read<R: BufRead + BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
-        self.magic = reader.read_fixed_string(4)?;
-        if self.magic != "mohd" {
+        self.read_with_endianness(reader, None)
+    }
+
+    /// Reads the header as `endianness` when given, otherwise in the byte order detected from the magic and
+    /// version, see `Endianness::detect`.
+    pub fn read_with_endianness<R: BufRead + BufReaderExt>(
+        &mut self,
+        reader: &mut R,
+        endianness: Option<Endianness>,
+    ) -> Result<()> {
+        // Read by hand rather than with `read_fixed_string`, whose `UnexpectedEof` would hide that the file is
+        // simply not a module.
+        let mut magic = Vec::with_capacity(4);
+        reader.by_ref().take(4).read_to_end(&mut magic)?;
+        if magic.len() < 4 {
+            bail!(ModuleError::NotAModule { size: magic.len() })
+        }
+        self.magic = String::from_utf8_lossy(&magic)
+            .trim_end_matches('\0')
+            .to_string();
+        if self.magic != "mohd" && self.magic != "dhom" {
             bail!(ModuleError::InvalidModuleMagic(self.magic.clone()))
         }
-        self.version = reader.read_u32::<LE>()?;
-        if self.version != 27 && self.version != 23 {
-            bail!(ModuleError::InvalidModuleVersion(self.version))
-        }
-        self.module_id = reader.read_u64::<LE>()?;
-        self.item_count = reader.read_u32::<LE>()?;
-        self.manifest_count = reader.read_u32::<LE>()?;
-        self.resource_index = reader.read_i32::<LE>()?;
-        self.strings_size = reader.read_u32::<LE>()?;
-        self.resource_count = reader.read_u32::<LE>()?;
-        self.block_count = reader.read_u32::<LE>()?;
-        self.build_version = reader.read_u64::<LE>()?;
-        if self.version == 27 {
-            self.checksum = reader.read_u64::<LE>()?;
+        let mut version = [0; 4];
+        reader.read_exact(&mut version)?;
+        self.endianness = endianness.unwrap_or_else(|| Endianness::detect(&self.magic, version));
+        match self.endianness {
+            Endianness::Little => self.read_fields::<LE, _>(reader, version),
+            Endianness::Big => self.read_fields::<BE, _>(reader, version),
+        }
+    }
+
+    fn read_fields<B: ByteOrder, R: BufRead>(
+        &mut self,
+        reader: &mut R,
+        version: [u8; 4],
+    ) -> Result<()> {
+        self.version = B::read_u32(&version);
+        let version = ModuleVersion::try_from(self.version)?;
+        self.module_id = reader.read_u64::<B>()?;
+        self.item_count = reader.read_u32::<B>()?;
+        self.manifest_count = reader.read_u32::<B>()?;
+        self.resource_index = reader.read_i32::<B>()?;
+        self.strings_size = reader.read_u32::<B>()?;
+        self.resource_count = reader.read_u32::<B>()?;
+        self.block_count = reader.read_u32::<B>()?;
+        self.build_version = reader.read_u64::<B>()?;
+        if version.has_checksums() {
+            self.checksum = reader.read_u64::<B>()?;
         }
         Ok(())
     }
+
+    /// Writes the header in the layout `read` expects, with a magic that `Endianness::detect` maps back to `B`.
+    pub fn write<B: ByteOrder, W: Write>(&self, writer: &mut W) -> Result<()> {
+        let version = ModuleVersion::try_from(self.version)?;
+        writer.write_u32::<B>(u32::from_le_bytes(*b"mohd"))?;
+        writer.write_u32::<B>(self.version)?;
+        writer.write_u64::<B>(self.module_id)?;
+        writer.write_u32::<B>(self.item_count)?;
+        writer.write_u32::<B>(self.manifest_count)?;
+        writer.write_i32::<B>(self.resource_index)?;
+        writer.write_u32::<B>(self.strings_size)?;
+        writer.write_u32::<B>(self.resource_count)?;
+        writer.write_u32::<B>(self.block_count)?;
+        writer.write_u64::<B>(self.build_version)?;
+        if version.has_checksums() {
+            writer.write_u64::<B>(self.checksum)?;
+        }
+        Ok(())
+    }
+
+    /// Opens the module at `path` and reads only its header.
+    pub fn open_header_only(path: &Path) -> Result<ModuleHeader> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = ModuleHeader::default();
+        header.read(&mut reader)?;
+        Ok(header)
+    }
+
+    /// Returns `build_version` formatted for display, in decimal followed by hex.
+    ///
+    /// How the build number is encoded is not known, so it is not decoded any further. The hex form makes fields
+    /// packed into it easier to spot when comparing modules of different patches.
+    pub fn build_string(&self) -> String {
+        format!("{} ({:#x})", self.build_version, self.build_version)
+    }
+
+    /// Returns the size of the header on disk, which depends on the version.
+    pub fn size(&self) -> Result<usize, ModuleError> {
+        Ok(self.version_kind()?.header_size())
+    }
+
+    /// Returns the typed version of the module, failing with `InvalidModuleVersion` for a header that was not
+    /// read successfully.
+    pub fn version_kind(&self) -> Result<ModuleVersion, ModuleError> {
+        ModuleVersion::try_from(self.version)
+    }
+}
+
+/// One aligned `field: value` line per header field, for eyeballing a header.
+impl fmt::Display for ModuleHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = ModuleVersion::try_from(self.version);
+        let title = version
+            .as_ref()
+            .map_or("unknown".to_string(), |version| version.title().to_string());
+        writeln!(f, "magic:          {}", self.magic)?;
+        writeln!(f, "version:        {} ({title})", self.version)?;
+        writeln!(f, "module_id:      {:#018x}", self.module_id)?;
+        writeln!(f, "item_count:     {}", self.item_count)?;
+        writeln!(f, "manifest_count: {}", self.manifest_count)?;
+        writeln!(f, "strings_size:   {}", self.strings_size)?;
+        writeln!(f, "resource_count: {}", self.resource_count)?;
+        writeln!(f, "block_count:    {}", self.block_count)?;
+        writeln!(f, "build_version:  {}", self.build_string())?;
+        if version.is_ok_and(ModuleVersion::has_checksums) {
+            write!(f, "checksum:       {:#018x}", self.checksum)
+        } else {
+            write!(f, "checksum:       -")
+        }
+    }
 }
 
 bitflags! {
-    #[derive(Default, Debug)]
+    #[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
     pub struct FileFlags: u8 {
         const COMPRESSED = 1 << 0;
         const HAS_BLOCKS = 1 << 1;
@@ -67,7 +349,65 @@ bitflags! {
     }
 }
 
-#[derive(Default, Debug)]
+/// Serializes as the list of set flag names, with any unknown bits appended as a single hex value.
+#[cfg(feature = "serde")]
+impl Serialize for FileFlags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut names: Vec<String> = self
+            .iter_names()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let unknown = self.bits() & !FileFlags::all().bits();
+        if unknown != 0 {
+            names.push(format!("{unknown:#04x}"));
+        }
+        names.serialize(serializer)
+    }
+}
+
+/// Accepts the representation written by `Serialize`.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for FileFlags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut flags = FileFlags::empty();
+        for name in Vec::<String>::deserialize(deserializer)? {
+            let flag = match name.strip_prefix("0x") {
+                Some(bits) => u8::from_str_radix(bits, 16)
+                    .ok()
+                    .map(FileFlags::from_bits_retain),
+                None => FileFlags::from_name(&name),
+            };
+            flags |=
+                flag.ok_or_else(|| serde::de::Error::custom(format!("unknown file flag {name}")))?;
+        }
+        Ok(flags)
+    }
+}
+
+/// Describes the representation written by `Serialize`.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for FileFlags {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "FileFlags".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "Names of the set flags, with any unknown bits as a single hex value.",
+            "type": "array",
+            "items": {
+                "anyOf": [
+                    { "enum": ["COMPRESSED", "HAS_BLOCKS", "RAW_FILE"] },
+                    { "type": "string", "pattern": "^0x[0-9a-f]+$" }
+                ]
+            }
+        })
+    }
+}
+
+#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ModuleFileEntry {
     pub name_offset: u32,
     pub parent_file_index: i32,
@@ -86,6 +426,10 @@ pub struct ModuleFileEntry {
     pub asset_id: i64,
     pub asset_checksum: i64,
     pub group_tag: String,
+    /// The on-disk value behind `group_tag`, which loses NUL padding and invalid UTF-8. `write` writes it back
+    /// as is while it still decodes to `group_tag`. `None` for entries that were not read from a module.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub group_tag_raw: Option<u32>,
     pub uncompressed_header_size: u32,
     pub uncompressed_tag_size: u32,
     pub uncompressed_resource_size: u32,
@@ -94,52 +438,116 @@ pub struct ModuleFileEntry {
     pub resource_block_count: i16,
     pub padding: i16,
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub data: Vec<u8>,
+    /// The resource section of `data`, filled in alongside it by `read_tag`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub resource_data: Vec<u8>,
 }
 
 impl ModuleFileEntry {
-    pub fn read<R: BufRead + BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
-        self.name_offset = reader.read_u32::<LE>()?;
-        self.parent_file_index = reader.read_i32::<LE>()?;
-        self.resource_count = reader.read_u32::<LE>()?;
-        self.first_resource_index = reader.read_i32::<LE>()?;
-        self.block_count = reader.read_u32::<LE>()?;
-        self.first_block_index = reader.read_i32::<LE>()?;
-        self.data_offset = reader.read_u64::<LE>()?;
-        self.total_compressed_size = reader.read_u32::<LE>()?;
-        self.total_uncompressed_size = reader.read_u32::<LE>()?;
+    pub fn read<B: ByteOrder, R: BufRead + BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        self.name_offset = reader.read_u32::<B>()?;
+        self.parent_file_index = reader.read_i32::<B>()?;
+        self.resource_count = reader.read_u32::<B>()?;
+        self.first_resource_index = reader.read_i32::<B>()?;
+        self.block_count = reader.read_u32::<B>()?;
+        self.first_block_index = reader.read_i32::<B>()?;
+        self.data_offset = reader.read_u64::<B>()?;
+        self.total_compressed_size = reader.read_u32::<B>()?;
+        self.total_uncompressed_size = reader.read_u32::<B>()?;
         self.header_alignment = reader.read_u8()?;
         self.tag_alignment = reader.read_u8()?;
         self.resource_alignment = reader.read_u8()?;
-        self.flags = FileFlags::from_bits_truncate(reader.read_u8()?);
-        self.global_tag_id = reader.read_i32::<LE>()?;
-        self.asset_id = reader.read_i64::<LE>()?;
-        self.asset_checksum = reader.read_i64::<LE>()?;
-        self.group_tag = reader.read_fixed_string(4)?.chars().rev().collect();
-        self.uncompressed_header_size = reader.read_u32::<LE>()?;
-        self.uncompressed_tag_size = reader.read_u32::<LE>()?;
-        self.uncompressed_resource_size = reader.read_u32::<LE>()?;
-        self.header_block_count = reader.read_i16::<LE>()?;
-        self.tag_block_count = reader.read_i16::<LE>()?;
-        self.resource_block_count = reader.read_i16::<LE>()?;
-        self.padding = reader.read_i16::<LE>()?;
+        self.flags = FileFlags::from_bits_retain(reader.read_u8()?);
+        self.global_tag_id = reader.read_i32::<B>()?;
+        self.asset_id = reader.read_i64::<B>()?;
+        self.asset_checksum = reader.read_i64::<B>()?;
+        let group_tag = reader.read_u32::<B>()?;
+        self.group_tag = GroupTag::from_le_bytes(group_tag.to_le_bytes()).to_string();
+        self.group_tag_raw = Some(group_tag);
+        self.uncompressed_header_size = reader.read_u32::<B>()?;
+        self.uncompressed_tag_size = reader.read_u32::<B>()?;
+        self.uncompressed_resource_size = reader.read_u32::<B>()?;
+        self.header_block_count = reader.read_i16::<B>()?;
+        self.tag_block_count = reader.read_i16::<B>()?;
+        self.resource_block_count = reader.read_i16::<B>()?;
+        self.padding = reader.read_i16::<B>()?;
         Ok(())
     }
 
-    pub fn read_name<R: BufRead + BufReaderExt + Seek>(
-        &mut self,
-        reader: &mut R,
-        file_name_offset: u32,
-    ) -> Result<()> {
-        reader.seek(SeekFrom::Start(
-            (file_name_offset + self.name_offset) as u64,
-        ))?;
-        self.name = reader.read_cstring()?;
+    /// Writes the entry in the layout `read` expects. The name is not part of the entry, only its `name_offset`.
+    pub fn write<B: ByteOrder, W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u32::<B>(self.name_offset)?;
+        writer.write_i32::<B>(self.parent_file_index)?;
+        writer.write_u32::<B>(self.resource_count)?;
+        writer.write_i32::<B>(self.first_resource_index)?;
+        writer.write_u32::<B>(self.block_count)?;
+        writer.write_i32::<B>(self.first_block_index)?;
+        writer.write_u64::<B>(self.data_offset)?;
+        writer.write_u32::<B>(self.total_compressed_size)?;
+        writer.write_u32::<B>(self.total_uncompressed_size)?;
+        writer.write_u8(self.header_alignment)?;
+        writer.write_u8(self.tag_alignment)?;
+        writer.write_u8(self.resource_alignment)?;
+        writer.write_u8(self.flags.bits())?;
+        writer.write_i32::<B>(self.global_tag_id)?;
+        writer.write_i64::<B>(self.asset_id)?;
+        writer.write_i64::<B>(self.asset_checksum)?;
+        let group_tag = match self.group_tag_raw {
+            Some(raw)
+                if GroupTag::from_le_bytes(raw.to_le_bytes()).to_string() == self.group_tag =>
+            {
+                raw
+            }
+            _ => {
+                let mut group_tag = self.group().to_bytes();
+                group_tag.reverse();
+                u32::from_le_bytes(group_tag)
+            }
+        };
+        writer.write_u32::<B>(group_tag)?;
+        writer.write_u32::<B>(self.uncompressed_header_size)?;
+        writer.write_u32::<B>(self.uncompressed_tag_size)?;
+        writer.write_u32::<B>(self.uncompressed_resource_size)?;
+        writer.write_i16::<B>(self.header_block_count)?;
+        writer.write_i16::<B>(self.tag_block_count)?;
+        writer.write_i16::<B>(self.resource_block_count)?;
+        writer.write_i16::<B>(self.padding)?;
+        Ok(())
+    }
+
+    /// Returns `group_tag` as a typed `GroupTag`.
+    pub fn group(&self) -> GroupTag {
+        GroupTag::from_fourcc(&self.group_tag)
+    }
+
+    /// Returns the flag bits that are not part of `FileFlags`, zero when every bit is known.
+    pub fn unknown_flag_bits(&self) -> u8 {
+        self.flags.bits() & !FileFlags::all().bits()
+    }
+
+    /// Reads this entry's name out of `strings`, the module's whole string table. Invalid UTF-8 is replaced
+    /// with U+FFFD.
+    pub fn read_name(&mut self, strings: &[u8]) -> Result<()> {
+        if self.name_offset as usize >= strings.len() {
+            bail!(ModuleError::NameOffsetOutOfBounds {
+                offset: self.name_offset,
+                size: strings.len() as u32,
+            })
+        }
+        let name = &strings[self.name_offset as usize..];
+        let name = match name.iter().position(|&byte| byte == 0) {
+            Some(end) => &name[..end],
+            None => name,
+        };
+        self.name = String::from_utf8_lossy(name).into_owned();
         Ok(())
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ModuleBlock {
     pub checksum: u64,
     pub compressed_offset: u32,
@@ -147,129 +555,1016 @@ pub struct ModuleBlock {
     pub uncompressed_offset: u32,
     pub uncompressed_size: u32,
     pub compressed: bool,
+    /// The on-disk value behind `compressed`, anything but 0 or 1 points at a misaligned block table.
+    pub compressed_raw: u32,
     pub padding: i32,
 }
 
 impl ModuleBlock {
-    pub fn read<R: BufRead + BufReaderExt + Seek>(
+    /// Returns the number of bytes the block occupies in the module: `compressed_size` for a compressed block,
+    /// `uncompressed_size` for one stored as is.
+    pub fn stored_size(&self) -> u32 {
+        if self.compressed {
+            self.compressed_size
+        } else {
+            self.uncompressed_size
+        }
+    }
+
+    pub fn read<B: ByteOrder, R: BufRead + BufReaderExt>(
         &mut self,
         reader: &mut R,
-        is_forge: bool,
+        version: ModuleVersion,
+    ) -> Result<()> {
+        if version.has_checksums() {
+            self.checksum = reader.read_u64::<B>()?;
+        }
+        self.compressed_offset = reader.read_u32::<B>()?;
+        self.compressed_size = reader.read_u32::<B>()?;
+        self.uncompressed_offset = reader.read_u32::<B>()?;
+        self.uncompressed_size = reader.read_u32::<B>()?;
+        self.compressed_raw = reader.read_u32::<B>()?;
+        self.compressed = self.compressed_raw != 0;
+        if version.has_checksums() {
+            self.padding = reader.read_i32::<B>()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the block in the layout `read` expects, with `compressed_raw` as the compressed flag.
+    pub fn write<B: ByteOrder, W: Write>(
+        &self,
+        writer: &mut W,
+        version: ModuleVersion,
     ) -> Result<()> {
-        if is_forge {
-            self.checksum = reader.read_u64::<LE>()?;
+        if version.has_checksums() {
+            writer.write_u64::<B>(self.checksum)?;
         }
-        self.compressed_offset = reader.read_u32::<LE>()?;
-        self.compressed_size = reader.read_u32::<LE>()?;
-        self.uncompressed_offset = reader.read_u32::<LE>()?;
-        self.uncompressed_size = reader.read_u32::<LE>()?;
-        self.compressed = reader.read_u32::<LE>()? != 0;
-        if is_forge {
-            self.padding = reader.read_i32::<LE>()?;
+        writer.write_u32::<B>(self.compressed_offset)?;
+        writer.write_u32::<B>(self.compressed_size)?;
+        writer.write_u32::<B>(self.uncompressed_offset)?;
+        writer.write_u32::<B>(self.uncompressed_size)?;
+        writer.write_u32::<B>(self.compressed_raw)?;
+        if version.has_checksums() {
+            writer.write_i32::<B>(self.padding)?;
         }
         Ok(())
     }
+
+    /// Returns whether the raw compressed flag is one of the expected values 0 and 1.
+    pub fn has_valid_compressed_flag(&self) -> bool {
+        self.compressed_raw <= 1
+    }
+}
+
+/// Key to order file entries by, see `H5Module::sorted_indices`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum EntryOrder {
+    /// By name.
+    Name,
+    /// By group tag, then name.
+    Group,
+    /// By `asset_id`.
+    AssetId,
+    /// By offset of the entry's data.
+    Offset,
+}
+
+/// The consecutive sections the decompressed data of a tag is made of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagSection {
+    Header,
+    Tag,
+    Resource,
+}
+
+impl TagSection {
+    /// Every section, in the order they are stored.
+    pub const ALL: [TagSection; 3] = [TagSection::Header, TagSection::Tag, TagSection::Resource];
+
+    /// Lowercase name of the section, also used as the extension of split section files.
+    pub fn name(self) -> &'static str {
+        match self {
+            TagSection::Header => "header",
+            TagSection::Tag => "tag",
+            TagSection::Resource => "resource",
+        }
+    }
 }
 
 #[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct H5Module {
     pub header: ModuleHeader,
+    /// The file table. Changing the names or ids of its entries after a `find_by_*` lookup leaves the lookup
+    /// tables stale until `invalidate_lookups` is called.
     pub files: Vec<ModuleFileEntry>,
+    /// Indices into `files` of the module's manifest (top-level) tags, stored right after the file table.
+    pub manifest_indices: Vec<i32>,
     pub resource_indices: Vec<i32>,
     pub blocks: Vec<ModuleBlock>,
     pub data_offset: u64,
+    /// Length of the module file in bytes, used to reject data ranges past its end. Set by `from_reader` and
+    /// `read_file_len`, `None` after a plain `read`, in which case ranges are not checked up front.
+    pub file_len: Option<u64>,
+    /// When set before `read`, a name table that fails to resolve for every file is replaced with synthetic
+    /// `<index>_<group>` names instead of failing the parse. That covers zeroed tables and tables whose offsets
+    /// all fall outside them; invalid UTF-8 is decoded lossily instead, see `lossy_strings`.
+    pub lenient_names: bool,
+    /// When set before `read`, the module is read in this byte order instead of the one detected from its header.
+    pub endianness: Option<Endianness>,
+    /// When set, extracting a tag that declares a larger uncompressed size fails with `SizeLimitExceeded` before
+    /// anything is allocated for it. Kept by `read`.
+    pub max_uncompressed_size: Option<u64>,
+    /// Set by `read` when the names of this module were synthesized.
+    pub synthetic_names: bool,
+    /// Set by `read` to the number of files whose name or group tag contained invalid UTF-8, which is
+    /// replaced with U+FFFD.
+    pub lossy_strings: usize,
+    // Lookup tables of the `find_by_*` methods, each built from `files` on its first use and cached.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    name_lookup: OnceCell<HashMap<String, usize>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    tag_id_lookup: OnceCell<HashMap<i32, usize>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    asset_id_lookup: OnceCell<HashMap<i64, usize>>,
 }
 
 impl H5Module {
-    pub fn read<R: BufRead + BufReaderExt + Seek>(&mut self, reader: &mut R) -> Result<()> {
-        self.header.read(reader)?;
-        self.files = (0..self.header.item_count)
-            .map(|_| {
-                let mut file = ModuleFileEntry::default();
-                file.read(reader).unwrap();
-                file
+    /// Creates an empty module with room for the given number of files, blocks and resource indices.
+    pub fn with_capacity(item_count: usize, block_count: usize, resource_count: usize) -> Self {
+        H5Module {
+            files: Vec::with_capacity(item_count),
+            resource_indices: Vec::with_capacity(resource_count),
+            blocks: Vec::with_capacity(block_count),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the title the module was built for.
+    ///
+    /// `None` if the module has not been read successfully, see `ModuleHeader::version_kind`.
+    pub fn title(&self) -> Option<Title> {
+        self.header.version_kind().ok().map(ModuleVersion::title)
+    }
+
+    /// Returns whether file `file_index` is listed in the module's manifest.
+    pub fn is_manifest(&self, file_index: usize) -> bool {
+        self.manifest_indices
+            .iter()
+            .any(|&index| index >= 0 && index as usize == file_index)
+    }
+
+    /// Iterates over the file table. Parsing a module never extracts data, so this only walks metadata; use
+    /// `extract_tag` to decompress the entries that are needed.
+    pub fn entries(&self) -> impl Iterator<Item = &ModuleFileEntry> {
+        self.files.iter()
+    }
+
+    /// Returns the indices of `files` ordered by `order`. Entries with equal keys keep their table order.
+    pub fn sorted_indices(&self, order: EntryOrder) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.files.len()).collect();
+        let files = &self.files;
+        match order {
+            EntryOrder::Name => indices.sort_by(|&a, &b| files[a].name.cmp(&files[b].name)),
+            EntryOrder::Group => indices.sort_by(|&a, &b| {
+                (&files[a].group_tag, &files[a].name).cmp(&(&files[b].group_tag, &files[b].name))
+            }),
+            EntryOrder::AssetId => indices.sort_by_key(|&index| files[index].asset_id),
+            EntryOrder::Offset => indices.sort_by_key(|&index| files[index].data_offset),
+        }
+        indices
+    }
+
+    /// Returns the resource entries listed by file `parent` through its slice of `resource_indices`.
+    ///
+    /// Slots that fall outside `resource_indices` or point outside the file table are left out.
+    pub fn resources_of(&self, parent: usize) -> Vec<&ModuleFileEntry> {
+        let file = &self.files[parent];
+        if file.first_resource_index < 0 {
+            return Vec::new();
+        }
+        let first = file.first_resource_index as usize;
+        self.resource_indices
+            .iter()
+            .skip(first)
+            .take(file.resource_count as usize)
+            .filter_map(|&index| usize::try_from(index).ok())
+            .filter_map(|index| self.files.get(index))
+            .collect()
+    }
+
+    /// Returns the blocks of file `index`, failing with `BlockRangeOutOfBounds` when its block range does not
+    /// fit in the block table.
+    pub fn file_blocks(&self, index: usize) -> Result<&[ModuleBlock]> {
+        Ok(&self.blocks[self.block_range(index)?])
+    }
+
+    /// Returns the range of `blocks` that belongs to file `index`, see `file_blocks`.
+    fn block_range(&self, index: usize) -> Result<Range<usize>, ModuleError> {
+        let file = &self.files[index];
+        usize::try_from(file.first_block_index)
+            .ok()
+            .and_then(|first| Some(first..first.checked_add(file.block_count as usize)?))
+            .filter(|range| range.end <= self.blocks.len())
+            .ok_or_else(|| ModuleError::BlockRangeOutOfBounds {
+                file: file.name.clone(),
+                first: file.first_block_index,
+                count: file.block_count,
+                total: self.blocks.len(),
             })
-            .collect();
+    }
 
-        let name_offset = reader.stream_position()?;
+    /// Checks that the stored regions of every file's blocks lie within the file's `total_compressed_size` and do
+    /// not overlap, failing with `BlockLayoutMismatch` for the first offending block. Block ranges that do not fit
+    /// the block table fail with `BlockRangeOutOfBounds`.
+    ///
+    /// Only the table is inspected, nothing is read or decompressed. Files stored verbatim are skipped.
+    pub fn validate_layout(&self) -> Result<(), ModuleError> {
+        self.check_layout(false)
+    }
 
-        for file in &mut self.files {
-            file.read_name(reader, name_offset as u32)?
+    /// Same as `validate_layout`, but also requires the blocks of every file to cover its
+    /// `total_compressed_size` without gaps.
+    pub fn validate_contiguous_layout(&self) -> Result<(), ModuleError> {
+        self.check_layout(true)
+    }
+
+    fn check_layout(&self, contiguous: bool) -> Result<(), ModuleError> {
+        for (index, file) in self.files.iter().enumerate() {
+            if !file.flags.contains(FileFlags::HAS_BLOCKS)
+                || file.flags.contains(FileFlags::RAW_FILE)
+            {
+                continue;
+            }
+            let range = self.block_range(index)?;
+            let mut regions: Vec<(usize, u64, u64)> = self.blocks[range.clone()]
+                .iter()
+                .zip(range)
+                .map(|(block, block_index)| {
+                    let size = if block.compressed {
+                        block.compressed_size
+                    } else {
+                        block.uncompressed_size
+                    };
+                    let start = block.compressed_offset as u64;
+                    (block_index, start, start + size as u64)
+                })
+                .collect();
+            regions.sort_by_key(|&(_, start, _)| start);
+
+            let mismatch = |block, detail| ModuleError::BlockLayoutMismatch {
+                file: file.name.clone(),
+                block,
+                detail,
+            };
+            let total = file.total_compressed_size as u64;
+            let mut previous: Option<(usize, u64)> = None;
+            for &(block, start, end) in &regions {
+                if end > total {
+                    return Err(mismatch(
+                        block,
+                        format!(
+                            "spans bytes {start}..{end}, past the {total} stored bytes of the tag"
+                        ),
+                    ));
+                }
+                if let Some((other, other_end)) =
+                    previous.filter(|&(_, other_end)| start < other_end)
+                {
+                    return Err(mismatch(
+                        block,
+                        format!(
+                            "starts at {start}, inside block {other} which ends at {other_end}"
+                        ),
+                    ));
+                }
+                let previous_end = previous.map_or(0, |(_, end)| end);
+                if contiguous && start != previous_end {
+                    return Err(mismatch(
+                        block,
+                        format!("starts at {start}, leaving a gap after byte {previous_end}"),
+                    ));
+                }
+                previous = Some((block, end));
+            }
+            if let Some((block, end)) = previous.filter(|&(_, end)| contiguous && end != total) {
+                return Err(mismatch(
+                    block,
+                    format!(
+                        "ends at {end}, leaving the tag's stored bytes up to {total} uncovered"
+                    ),
+                ));
+            }
         }
+        Ok(())
+    }
 
-        self.resource_indices = (0..self.header.resource_count)
-            .map(|_| reader.read_i32::<LE>().unwrap())
-            .collect();
+    /// Returns file `index`'s slice of `resource_indices`, failing with `ResourceIndexOutOfBounds` when it does not
+    /// fit in the table. Files without resources, whose `first_resource_index` is -1, get an empty slice.
+    pub fn file_resources(&self, index: usize) -> Result<&[i32]> {
+        let file = &self.files[index];
+        if file.first_resource_index < 0 && file.resource_count == 0 {
+            return Ok(&[]);
+        }
+        let range = usize::try_from(file.first_resource_index)
+            .ok()
+            .and_then(|first| Some(first..first.checked_add(file.resource_count as usize)?))
+            .filter(|range| range.end <= self.resource_indices.len());
+        match range {
+            Some(range) => Ok(&self.resource_indices[range]),
+            None => bail!(ModuleError::ResourceIndexOutOfBounds {
+                referrer: format!("Tag '{}'", file.name),
+                index: file.first_resource_index as i64 + file.resource_count as i64 - 1,
+                table: "resource table",
+                total: self.resource_indices.len(),
+            }),
+        }
+    }
 
-        self.blocks = (0..self.header.block_count)
-            .map(|_| {
-                let mut block = ModuleBlock::default();
-                block.read(reader, self.header.version == 27).unwrap();
-                block
+    /// Checks the header's `resource_index` and every file's resource slice and the tags it points at, returning
+    /// one `ResourceIndexOutOfBounds` per reference that falls outside its table.
+    pub fn check_resource_indices(&self) -> Vec<ModuleError> {
+        let mut errors = Vec::new();
+        let resource_index = self.header.resource_index;
+        if resource_index < -1 || resource_index as i64 >= self.header.item_count as i64 {
+            errors.push(ModuleError::ResourceIndexOutOfBounds {
+                referrer: "The module header".to_string(),
+                index: resource_index as i64,
+                table: "file table",
+                total: self.header.item_count as usize,
+            });
+        }
+        for (index, file) in self.files.iter().enumerate() {
+            let resources = match self.file_resources(index) {
+                Ok(resources) => resources,
+                Err(error) => {
+                    errors.extend(error.downcast::<ModuleError>().ok());
+                    continue;
+                }
+            };
+            for (slot, &resource) in resources.iter().enumerate() {
+                if usize::try_from(resource).map_or(true, |resource| resource >= self.files.len()) {
+                    errors.push(ModuleError::ResourceIndexOutOfBounds {
+                        referrer: format!("Resource {slot} of tag '{}'", file.name),
+                        index: resource as i64,
+                        table: "file table",
+                        total: self.files.len(),
+                    });
+                }
+            }
+        }
+        errors
+    }
+
+    /// Returns the range of file `index`'s decompressed data that holds its resource section, see
+    /// `section_range`.
+    pub fn resource_range(&self, index: usize) -> Result<Range<usize>> {
+        self.section_range(index, TagSection::Resource)
+    }
+
+    /// Returns the range of file `index`'s decompressed data that holds `section`, as long as the section's
+    /// `uncompressed_*_size`.
+    ///
+    /// For entries split into blocks the section starts at its first block: the header at the entry's first
+    /// block, the tag after the `header_block_count` header blocks and the resource at the first of the
+    /// `resource_block_count` last blocks. Other entries store the sections back to back, with the resource at
+    /// the end of their data.
+    pub fn section_range(&self, index: usize, section: TagSection) -> Result<Range<usize>> {
+        let file = &self.files[index];
+        let total = file.total_uncompressed_size as u64;
+        let header_size = file.uncompressed_header_size as u64;
+        let (size, block_count) = match section {
+            TagSection::Header => (header_size, file.header_block_count),
+            TagSection::Tag => (file.uncompressed_tag_size as u64, file.tag_block_count),
+            TagSection::Resource => (
+                file.uncompressed_resource_size as u64,
+                file.resource_block_count,
+            ),
+        };
+        let start = if file.flags.contains(FileFlags::HAS_BLOCKS)
+            && !file.flags.contains(FileFlags::RAW_FILE)
+            && block_count > 0
+            && size > 0
+        {
+            let blocks = self.file_blocks(index)?;
+            let first = match section {
+                TagSection::Header => Some(0),
+                TagSection::Tag => Some(file.header_block_count.max(0) as usize),
+                TagSection::Resource => blocks.len().checked_sub(block_count as usize),
+            };
+            let Some(block) = first.and_then(|first| blocks.get(first)) else {
+                bail!(ModuleError::SizeMismatch {
+                    file: file.name.clone(),
+                    size: file.total_uncompressed_size,
+                    detail: format!(
+                        "{block_count} {} blocks do not fit in its {} blocks",
+                        section.name(),
+                        blocks.len()
+                    ),
+                })
+            };
+            block.uncompressed_offset as u64
+        } else {
+            match section {
+                TagSection::Header => 0,
+                TagSection::Tag => header_size,
+                TagSection::Resource => total.saturating_sub(size),
+            }
+        };
+        if start.saturating_add(size) > total {
+            bail!(ModuleError::SectionOutOfBounds {
+                file: file.name.clone(),
+                section: section.name(),
+                start,
+                end: start.saturating_add(size),
+                size: file.total_uncompressed_size,
             })
-            .collect();
+        }
+        Ok(start as usize..(start + size) as usize)
+    }
+
+    /// Returns block `block_index` of file `file_index`, counted from the file's first block, together with the
+    /// absolute offset of its data in the module file. `None` when there is no such block or its offset does not
+    /// fit in a `u64`.
+    pub fn block_slice(
+        &self,
+        file_index: usize,
+        block_index: usize,
+    ) -> Option<(&ModuleBlock, u64)> {
+        let file = self.files.get(file_index)?;
+        let block = self.file_blocks(file_index).ok()?.get(block_index)?;
+        let offset = self
+            .data_offset
+            .checked_add(file.data_offset)?
+            .checked_add(block.compressed_offset as u64)?;
+        Some((block, offset))
+    }
 
-        self.data_offset = reader.stream_position()?;
+    /// Parses a module from `reader`, which can be a file, an in-memory `Cursor` or any other seekable source.
+    ///
+    /// `reader` must be positioned at the start of the module, see `read`.
+    pub fn from_reader<R: BufRead + BufReaderExt + Seek>(reader: &mut R) -> Result<Self> {
+        let mut module = H5Module::default();
+        module.read(reader)?;
+        module.read_file_len(reader)?;
+        Ok(module)
+    }
 
-        for id in 0..self.files.len() {
-            self.read_tag(id as u32, reader)?;
+    /// Parses the header, file table, names, resource indices and block table, leaving `reader` at the start of
+    /// the data section.
+    ///
+    /// Offsets are counted from the bytes consumed rather than queried with `stream_position`, so `reader` does
+    /// not need to seek, but the module must start at offset zero of `reader` and `reader` must be positioned
+    /// there. `file_len` is left unset, see `read_file_len`.
+    pub fn read<R: BufRead + BufReaderExt>(&mut self, reader: &mut R) -> Result<()> {
+        let reader = &mut CountingReader::new(reader);
+        self.header.read_with_endianness(reader, self.endianness)?;
+        // Reserved before `header` is moved out, the fields of the literal are evaluated in order.
+        let tables = H5Module::with_capacity(
+            self.header.item_count as usize,
+            self.header.block_count as usize,
+            self.header.resource_count as usize,
+        );
+        *self = H5Module {
+            header: std::mem::take(&mut self.header),
+            lenient_names: self.lenient_names,
+            endianness: self.endianness,
+            max_uncompressed_size: self.max_uncompressed_size,
+            ..tables
+        };
+        match self.header.endianness {
+            Endianness::Little => self.read_tables::<LE, _>(reader)?,
+            Endianness::Big => self.read_tables::<BE, _>(reader)?,
         }
+
+        self.lossy_strings = self
+            .files
+            .iter()
+            .filter(|file| {
+                file.name.contains(char::REPLACEMENT_CHARACTER)
+                    || file.group_tag.contains(char::REPLACEMENT_CHARACTER)
+            })
+            .count();
+
+        self.data_offset = reader.position();
         Ok(())
     }
 
+    /// Sets `file_len` to the length of the module `reader` reads from and returns `reader` to the start of the
+    /// data section.
+    pub fn read_file_len<R: Seek>(&mut self, reader: &mut R) -> io::Result<()> {
+        self.file_len = Some(reader.seek(SeekFrom::End(0))?);
+        reader.seek(SeekFrom::Start(self.data_offset))?;
+        Ok(())
+    }
+
+    /// Reads the file table, manifest indices, names, resource indices and block table that follow the header.
+    fn read_tables<B: ByteOrder, R: BufRead + BufReaderExt>(
+        &mut self,
+        reader: &mut R,
+    ) -> Result<()> {
+        for _ in 0..self.header.item_count {
+            let mut file = ModuleFileEntry::default();
+            file.read::<B, _>(reader)?;
+            self.files.push(file);
+        }
+
+        for _ in 0..self.header.manifest_count {
+            self.manifest_indices.push(reader.read_i32::<B>()?);
+        }
+
+        let mut strings = vec![0; self.header.strings_size as usize];
+        reader.read_exact(&mut strings)?;
+        if self.lenient_names {
+            self.read_names_lenient(&strings)?;
+        } else {
+            for file in &mut self.files {
+                file.read_name(&strings)?
+            }
+        }
+
+        for _ in 0..self.header.resource_count {
+            self.resource_indices.push(reader.read_i32::<B>()?);
+        }
+
+        let version = self.header.version_kind()?;
+        for _ in 0..self.header.block_count {
+            let mut block = ModuleBlock::default();
+            block.read::<B, _>(reader, version)?;
+            self.blocks.push(block);
+        }
+        Ok(())
+    }
+
+    /// Reads every name, falling back to synthetic names when none of them resolve.
+    ///
+    /// A name counts as unresolved when reading it fails or yields an empty string, as a zeroed table does.
+    /// Partial failures are still reported as errors, only a wholesale failure triggers the fallback.
+    fn read_names_lenient(&mut self, strings: &[u8]) -> Result<()> {
+        let mut first_error = None;
+        let mut resolved = 0;
+        for file in &mut self.files {
+            match file.read_name(strings) {
+                Ok(()) if !file.name.is_empty() => resolved += 1,
+                Ok(()) => {}
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+
+        if resolved == 0 && !self.files.is_empty() {
+            for (index, file) in self.files.iter_mut().enumerate() {
+                let group = file.group_tag.trim();
+                file.name = if group.is_empty() {
+                    index.to_string()
+                } else {
+                    format!("{index}_{group}")
+                };
+            }
+            self.synthetic_names = true;
+        } else if let Some(error) = first_error {
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Drops the cached lookup tables of the `find_by_*` methods, so they are rebuilt from `files` on their next
+    /// use.
+    pub fn invalidate_lookups(&mut self) {
+        self.name_lookup.take();
+        self.tag_id_lookup.take();
+        self.asset_id_lookup.take();
+    }
+
+    /// Returns the index of the first file named `name`.
+    pub fn find_by_name(&self, name: &str) -> Option<usize> {
+        self.name_lookup
+            .get_or_init(|| {
+                let mut lookup = HashMap::with_capacity(self.files.len());
+                for (index, file) in self.files.iter().enumerate() {
+                    lookup.entry(file.name.clone()).or_insert(index);
+                }
+                lookup
+            })
+            .get(name)
+            .copied()
+    }
+
+    /// Returns the index of the first file with the given `global_tag_id`.
+    pub fn find_by_tag_id(&self, global_tag_id: i32) -> Option<usize> {
+        self.tag_id_lookup
+            .get_or_init(|| {
+                let mut lookup = HashMap::with_capacity(self.files.len());
+                for (index, file) in self.files.iter().enumerate() {
+                    lookup.entry(file.global_tag_id).or_insert(index);
+                }
+                lookup
+            })
+            .get(&global_tag_id)
+            .copied()
+    }
+
+    /// Returns the first file with the given `global_tag_id`, see `find_by_tag_id`.
+    pub fn find_by_global_id(&self, global_tag_id: i32) -> Option<&ModuleFileEntry> {
+        self.find_by_tag_id(global_tag_id)
+            .map(|index| &self.files[index])
+    }
+
+    /// Returns the index of the first file with the given `asset_id`. Files without an asset id (-1) are never
+    /// found.
+    pub fn find_by_asset_id_index(&self, asset_id: i64) -> Option<usize> {
+        self.asset_id_lookup
+            .get_or_init(|| {
+                let mut lookup = HashMap::with_capacity(self.files.len());
+                for (index, file) in self.files.iter().enumerate() {
+                    if file.asset_id != -1 {
+                        lookup.entry(file.asset_id).or_insert(index);
+                    }
+                }
+                lookup
+            })
+            .get(&asset_id)
+            .copied()
+    }
+
+    /// Returns the first file with the given `asset_id`, see `find_by_asset_id_index`.
+    pub fn find_by_asset_id(&self, asset_id: i64) -> Option<&ModuleFileEntry> {
+        self.find_by_asset_id_index(asset_id)
+            .map(|index| &self.files[index])
+    }
+
+    /// Decompresses the file named `name` into its `data` field and returns its index.
+    pub fn extract_file_by_name<R: BufRead + Seek>(
+        &mut self,
+        name: &str,
+        reader: &mut R,
+    ) -> Result<Option<usize>> {
+        let Some(index) = self.find_by_name(name) else {
+            return Ok(None);
+        };
+        self.read_tag(index as u32, reader)?;
+        Ok(Some(index))
+    }
+
+    /// Decompresses the file with the given `global_tag_id` into its `data` field and returns its index.
+    pub fn extract_file_by_tag_id<R: BufRead + Seek>(
+        &mut self,
+        global_tag_id: i32,
+        reader: &mut R,
+    ) -> Result<Option<usize>> {
+        let Some(index) = self.find_by_tag_id(global_tag_id) else {
+            return Ok(None);
+        };
+        self.read_tag(index as u32, reader)?;
+        Ok(Some(index))
+    }
+
+    /// Decompresses file `index` into its `data` field, and its resource section into `resource_data`.
+    ///
+    /// When the entry's section sizes do not fit its data, `resource_data` is left empty and a warning is logged.
     pub fn read_tag<R: BufRead + Seek>(&mut self, index: u32, reader: &mut R) -> Result<()> {
+        self.read_tag_with_pool(index, reader, &mut BufferPool::default())
+    }
+
+    /// Same as `read_tag`, but takes its intermediate and output buffers from `pool`.
+    ///
+    /// Intermediate buffers are returned to the pool before this returns; the caller can give the tag's
+    /// `data` back once it is done with it.
+    pub fn read_tag_with_pool<R: BufRead + Seek>(
+        &mut self,
+        index: u32,
+        reader: &mut R,
+        pool: &mut BufferPool,
+    ) -> Result<()> {
+        let data = self.extract_tag_with_pool(index, reader, pool)?;
+        // Section sizes are not needed to decompress the tag, so bad ones only cost the resource section.
+        let resource = match self.resource_range(index as usize) {
+            Ok(resource) => data[resource].to_vec(),
+            Err(error) => {
+                log::warn!("{error}");
+                Vec::new()
+            }
+        };
         let file = &mut self.files[index as usize];
+        file.resource_data = resource;
+        file.data = data;
+        Ok(())
+    }
+
+    /// Decompresses file `index` and returns its bytes without storing them in the module.
+    pub fn extract_tag<R: BufRead + Seek>(&self, index: u32, reader: &mut R) -> Result<Vec<u8>> {
+        self.extract_tag_with_pool(index, reader, &mut BufferPool::default())
+    }
+
+    /// Same as `extract_tag`, but takes its intermediate and output buffers from `pool`.
+    ///
+    /// Intermediate buffers are returned to the pool before this returns; the caller can give the returned
+    /// buffer back once it is done with it.
+    pub fn extract_tag_with_pool<R: BufRead + Seek>(
+        &self,
+        index: u32,
+        reader: &mut R,
+        pool: &mut BufferPool,
+    ) -> Result<Vec<u8>> {
+        self.decompress_tag(index, reader, pool, &ZlibDecompressor, None)
+    }
+
+    /// Same as `extract_tag_with_pool`, but decompresses the tag's streams with `decompressor` instead of zlib.
+    pub fn extract_tag_with_decompressor<R: BufRead + Seek>(
+        &self,
+        index: u32,
+        reader: &mut R,
+        pool: &mut BufferPool,
+        decompressor: &dyn BlockDecompressor,
+    ) -> Result<Vec<u8>> {
+        self.decompress_tag(index, reader, pool, decompressor, None)
+    }
+
+    /// Same as `extract_tag_with_pool`, but also checks every decompressed block against its stored checksum
+    /// using `checksum`, failing with `ChecksumMismatch` on the first block that differs.
+    ///
+    /// Only Forge modules store block checksums, tags of campaign modules and tags without blocks are not
+    /// checked.
+    pub fn extract_tag_verified<R: BufRead + Seek>(
+        &self,
+        index: u32,
+        reader: &mut R,
+        pool: &mut BufferPool,
+        checksum: &dyn ChecksumCandidate,
+    ) -> Result<Vec<u8>> {
+        let verify = self
+            .header
+            .version_kind()?
+            .has_checksums()
+            .then_some(checksum);
+        self.decompress_tag(index, reader, pool, &ZlibDecompressor, verify)
+    }
+
+    /// Decompresses file `index` and writes it to `out`, returning the number of bytes written.
+    ///
+    /// Unlike `extract_tag` the tag is usually not held in memory as a whole: blocks are decompressed and written
+    /// one at a time and compressed tags without blocks are inflated straight into `out`. Only a block table that
+    /// lists the blocks out of offset order is assembled in memory first. The checks are the same as
+    /// `extract_tag`'s, but `out` may already have received part of the tag when one of them fails.
+    pub fn write_tag_to<R: BufRead + Seek, W: Write>(
+        &self,
+        index: u32,
+        reader: &mut R,
+        out: &mut W,
+    ) -> Result<u64> {
+        let file = &self.files[index as usize];
         if file.total_uncompressed_size == 0 {
             bail!(ModuleError::EmptyTag)
         }
+        self.check_size_limit(file)?;
 
         let block_offset = file.data_offset + self.data_offset;
+        self.check_data_range(file, block_offset, file.total_compressed_size as u64)?;
 
-        if file.flags.contains(FileFlags::HAS_BLOCKS) {
-            let mut data_buffer = vec![0u8; file.total_uncompressed_size as usize];
-
-            let blocks = &self.blocks[file.first_block_index as usize
-                ..(file.first_block_index + file.block_count as i32) as usize];
+        if stored_verbatim(file)? {
+            reader.seek(SeekFrom::Start(block_offset))?;
+            let size = file.total_compressed_size as u64;
+            if io::copy(&mut reader.take(size), out)? != size {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+            Ok(size)
+        } else if file.flags.contains(FileFlags::HAS_BLOCKS) {
+            if self.blocks.is_empty() {
+                bail!(ModuleError::MissingBlockTable(file.name.clone()))
+            }
+            let blocks = self.file_blocks(index as usize)?;
+            check_block_layout(file, blocks)?;
+            // Blocks are written in table order, which only reproduces the tag when that is also offset order.
+            if !blocks.is_sorted_by_key(|block| block.uncompressed_offset) {
+                let data = self.extract_tag(index, reader)?;
+                out.write_all(&data)?;
+                return Ok(data.len() as u64);
+            }
 
+            let mut block_buffer = Vec::new();
+            let mut output = Vec::new();
             for block in blocks {
-                let mut block_buffer = vec![0u8; block.compressed_size as usize];
                 let offset = block_offset + block.compressed_offset as u64;
+                self.check_data_range(file, offset, block.stored_size() as u64)?;
                 reader.seek(SeekFrom::Start(offset))?;
-                reader.read_exact(&mut block_buffer)?;
-
-                let cursor = Cursor::new(&block_buffer);
-                let buffer_reader = BufReader::new(cursor);
-                let mut output_buffer = vec![0u8; block.uncompressed_size as usize];
-
+                output.resize(block.uncompressed_size as usize, 0);
                 if block.compressed {
-                    let mut decompressor = ZlibDecoder::new(buffer_reader);
-                    decompressor.read_exact(&mut output_buffer)?;
+                    block_buffer.resize(block.compressed_size as usize, 0);
+                    reader.read_exact(&mut block_buffer)?;
+                    ZlibDecompressor.decompress(&block_buffer, &mut output)?;
                 } else {
-                    output_buffer.copy_from_slice(&block_buffer);
+                    reader.read_exact(&mut output)?;
                 }
-
-                let dest_start = block.uncompressed_offset as usize;
-                let dest_end = dest_start + block.uncompressed_size as usize;
-                data_buffer[dest_start..dest_end].copy_from_slice(&output_buffer);
+                out.write_all(&output)?;
             }
+            Ok(file.total_uncompressed_size as u64)
+        } else {
+            reader.seek(SeekFrom::Start(block_offset))?;
+            ZlibDecompressor.decompress_stream_to(
+                &mut reader.take(file.total_compressed_size as u64),
+                out,
+                file.total_uncompressed_size as u64,
+            )?;
+            Ok(file.total_uncompressed_size as u64)
+        }
+    }
 
-            file.data = data_buffer;
+    /// Returns the bytes of file `index` as they are stored in the module, without decompressing them.
+    ///
+    /// For entries split into blocks this is the stored data of every block in table order, which is the whole
+    /// `total_compressed_size` region unless the blocks leave gaps in it.
+    pub fn extract_raw<R: BufRead + Seek>(&self, index: usize, reader: &mut R) -> Result<Vec<u8>> {
+        let file = &self.files[index];
+        let data_start = file.data_offset + self.data_offset;
+        let ranges: Vec<(u64, u32)> = if file.flags.contains(FileFlags::HAS_BLOCKS)
+            && !file.flags.contains(FileFlags::RAW_FILE)
+        {
+            self.file_blocks(index)?
+                .iter()
+                .map(|block| {
+                    let size = if block.compressed {
+                        block.compressed_size
+                    } else {
+                        block.uncompressed_size
+                    };
+                    (data_start + block.compressed_offset as u64, size)
+                })
+                .collect()
         } else {
-            let mut file_buffer = vec![0u8; file.total_compressed_size as usize];
-            let offset = block_offset;
+            vec![(data_start, file.total_compressed_size)]
+        };
+
+        let mut data = Vec::with_capacity(file.total_compressed_size as usize);
+        for (offset, size) in ranges {
+            self.check_data_range(file, offset, size as u64)?;
             reader.seek(SeekFrom::Start(offset))?;
+            let start = data.len();
+            data.resize(start + size as usize, 0);
+            reader.read_exact(&mut data[start..])?;
+        }
+        Ok(data)
+    }
+
+    /// Fails with `DataOutOfBounds` when `size` bytes at `offset` do not fit in the module file.
+    pub(crate) fn check_data_range(
+        &self,
+        file: &ModuleFileEntry,
+        offset: u64,
+        size: u64,
+    ) -> Result<()> {
+        match self.file_len {
+            Some(file_len) if offset.saturating_add(size) > file_len => {
+                bail!(ModuleError::DataOutOfBounds {
+                    file: file.name.clone(),
+                    offset,
+                    size,
+                    file_len,
+                })
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Fails with `SizeLimitExceeded` when `file` declares more uncompressed bytes than `max_uncompressed_size`.
+    pub(crate) fn check_size_limit(&self, file: &ModuleFileEntry) -> Result<()> {
+        match self.max_uncompressed_size {
+            Some(limit) if file.total_uncompressed_size as u64 > limit => {
+                bail!(ModuleError::SizeLimitExceeded {
+                    file: file.name.clone(),
+                    size: file.total_uncompressed_size,
+                    limit,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn decompress_tag<R: BufRead + Seek>(
+        &self,
+        index: u32,
+        reader: &mut R,
+        pool: &mut BufferPool,
+        decompressor: &dyn BlockDecompressor,
+        verify: Option<&dyn ChecksumCandidate>,
+    ) -> Result<Vec<u8>> {
+        let file = &self.files[index as usize];
+        if file.total_uncompressed_size == 0 {
+            bail!(ModuleError::EmptyTag)
+        }
+        self.check_size_limit(file)?;
+
+        let block_offset = file.data_offset + self.data_offset;
+        self.check_data_range(file, block_offset, file.total_compressed_size as u64)?;
+
+        if stored_verbatim(file)? {
+            let mut file_buffer = pool.take(file.total_compressed_size as usize);
+            reader.seek(SeekFrom::Start(block_offset))?;
             reader.read_exact(&mut file_buffer)?;
+            Ok(file_buffer)
+        } else if file.flags.contains(FileFlags::HAS_BLOCKS) {
+            if self.blocks.is_empty() {
+                bail!(ModuleError::MissingBlockTable(file.name.clone()))
+            }
+            let mut data_buffer = pool.take(file.total_uncompressed_size as usize);
+            let mut block_buffer = pool.take(0);
+
+            let first_block = file.first_block_index as usize;
+            let blocks = self.file_blocks(index as usize)?;
+            check_block_layout(file, blocks)?;
+
+            for (block_index, block) in (first_block..).zip(blocks) {
+                let dest_start = block.uncompressed_offset as usize;
+                let dest_end = dest_start + block.uncompressed_size as usize;
+                let output = &mut data_buffer[dest_start..dest_end];
+
+                let offset = block_offset + block.compressed_offset as u64;
+                self.check_data_range(file, offset, block.stored_size() as u64)?;
+                reader.seek(SeekFrom::Start(offset))?;
+                if block.compressed {
+                    block_buffer.resize(block.compressed_size as usize, 0);
+                    reader.read_exact(&mut block_buffer)?;
+                    decompressor.decompress(&block_buffer, output)?;
+                } else {
+                    reader.read_exact(output)?;
+                }
 
-            if file.flags.contains(FileFlags::COMPRESSED) {
-                let mut decompressed_buffer = vec![0u8; file.total_uncompressed_size as usize];
-                let mut decompressor = ZlibDecoder::new(&file_buffer[..]);
-                decompressor.read_exact(&mut decompressed_buffer)?;
-                file.data = decompressed_buffer;
-            } else {
-                bail!(ModuleError::NonCompressedSingleTag)
+                if let Some(checksum) = verify {
+                    let actual = checksum.compute(output);
+                    if actual != block.checksum {
+                        bail!(ModuleError::ChecksumMismatch {
+                            file: file.name.clone(),
+                            block: block_index,
+                            expected: block.checksum,
+                            actual,
+                        })
+                    }
+                }
             }
+            pool.give(block_buffer);
+
+            Ok(data_buffer)
+        } else {
+            // Inflated straight from the reader, limited to the compressed size so a malformed stream cannot
+            // read past the entry, into a buffer of exactly the uncompressed size.
+            reader.seek(SeekFrom::Start(block_offset))?;
+            let mut decompressed_buffer = pool.take(file.total_uncompressed_size as usize);
+            decompressor.decompress_stream(
+                &mut reader.take(file.total_compressed_size as u64),
+                &mut decompressed_buffer,
+            )?;
+            Ok(decompressed_buffer)
         }
+    }
+}
 
-        Ok(())
+/// Returns whether `file` is stored verbatim rather than compressed.
+///
+/// RAW_FILE takes precedence over every other flag: the data is stored verbatim even if COMPRESSED or HAS_BLOCKS
+/// is also set. Otherwise HAS_BLOCKS wins over COMPRESSED, and an entry with neither is stored verbatim as well,
+/// which requires its compressed and uncompressed sizes to match.
+fn stored_verbatim(file: &ModuleFileEntry) -> Result<bool> {
+    let unflagged = !file
+        .flags
+        .intersects(FileFlags::COMPRESSED | FileFlags::HAS_BLOCKS);
+    if unflagged
+        && !file.flags.contains(FileFlags::RAW_FILE)
+        && file.total_compressed_size != file.total_uncompressed_size
+    {
+        bail!(ModuleError::NonCompressedSingleTag)
+    }
+    Ok(unflagged || file.flags.contains(FileFlags::RAW_FILE))
+}
+
+/// Checks that the uncompressed ranges of `blocks` follow each other without gaps or overlaps and add up to the
+/// size of `file`.
+///
+/// The ranges are checked in offset order rather than table order, each block is copied to its own
+/// `uncompressed_offset` so a table listing them out of order still reproduces the tag.
+fn check_block_layout(file: &ModuleFileEntry, blocks: &[ModuleBlock]) -> Result<()> {
+    let mismatch = |detail| ModuleError::SizeMismatch {
+        file: file.name.clone(),
+        size: file.total_uncompressed_size,
+        detail,
+    };
+    let mut order: Vec<usize> = (0..blocks.len()).collect();
+    order.sort_by_key(|&index| blocks[index].uncompressed_offset);
+    let mut end = 0u64;
+    for index in order {
+        let block = &blocks[index];
+        if block.uncompressed_offset as u64 != end {
+            bail!(mismatch(format!(
+                "block {index} starts at {}, expected {end}",
+                block.uncompressed_offset
+            )))
+        }
+        end += block.uncompressed_size as u64;
+    }
+    if end != file.total_uncompressed_size as u64 {
+        bail!(mismatch(format!("blocks add up to {end} bytes")))
     }
+    Ok(())
 }
@@ -1,55 +1,1852 @@
-use crate::loader::H5Module;
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{bail, Result};
+use clap::error::ErrorKind;
+use clap::{ArgAction, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use globset::{GlobBuilder, GlobMatcher};
+use h5_dumper::cache::IndexCache;
+use h5_dumper::checksum::ChecksumCandidate;
+use h5_dumper::common::{RetryPolicy, RetryingReader};
+use h5_dumper::diff::{self, Change, MatchKey};
+use h5_dumper::duplicates::{ContentDeduplicator, DedupReference, DuplicateKey, DuplicateScanner};
+use h5_dumper::export::{ManifestDocument, ModuleManifest};
+use h5_dumper::graph::{self, TagGraph};
+use h5_dumper::groups;
+use h5_dumper::hash::{self, Crc32Writer, HashAlgorithm};
+use h5_dumper::manifest::{self, EntryOutcome};
+use h5_dumper::pack::{self, PackManifest, PackOptions};
+use h5_dumper::paths::{
+    flat_name, sanitize_component, sanitize_path, with_group_extension, UniquePaths,
+};
+use h5_dumper::pool::BufferPool;
+use h5_dumper::report::ExtractionReport;
+use h5_dumper::{checksum, debug_dump, export, validate};
+use h5_dumper::{
+    BufReaderExt, Endianness, EntryOrder, FileFlags, H5Module, ModuleError, ModuleHeader,
+    ModuleVersion, TagSection,
+};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use log::{debug, error, info, warn, Level, LevelFilter, Log, Metadata, Record};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
 use std::fs::File;
-use std::io::{BufReader, Write};
-use std::path::Path;
+use std::io::{BufReader, BufWriter, Cursor, IsTerminal, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
-
-pub mod common;
-mod loader;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
 
 /// Halo 5 module dumper.
 /// Supports both Halo 5 Forge and Halo 5 campaign.
 #[derive(Parser, Debug)]
 #[command(version, about)]
 struct H5ModuleLoader {
-    /// Path to where modules are located (deploy folder).
-    #[arg(short, long)]
-    module_path: String,
+    #[command(subcommand)]
+    command: Command,
+    /// Path to where modules are located (deploy folder), or to a single module file. Required by every command
+    /// but diff and pack.
+    #[arg(short, long, global = true)]
+    module_path: Option<String>,
+    /// Only log warnings and errors, and don't draw progress bars or print the summaries of extract and diff.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Log more detail, such as every written file. Repeat for even more. `RUST_LOG` overrides both flags.
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Indent every JSON output. By default manifests are indented while graphs and header records are not.
+    /// Header records written to a `.jsonl` path always stay one per line.
+    #[arg(long, global = true, conflicts_with = "compact_json")]
+    pretty_json: bool,
+    /// Write every JSON output without whitespace.
+    #[arg(long, global = true)]
+    compact_json: bool,
+}
+
+impl H5ModuleLoader {
+    /// Returns the JSON style picked with --pretty-json or --compact-json, or `default` when neither was given.
+    fn json_style(&self, default: JsonStyle) -> JsonStyle {
+        if self.pretty_json {
+            JsonStyle::Pretty
+        } else if self.compact_json {
+            JsonStyle::Compact
+        } else {
+            default
+        }
+    }
+
+    /// Returns --module-path, exiting with a usage error when it was not given.
+    fn module_path(&self) -> &str {
+        self.module_path.as_deref().unwrap_or_else(|| {
+            H5ModuleLoader::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "the following required arguments were not provided:\n  --module-path <MODULE_PATH>",
+                )
+                .exit()
+        })
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Extract the files of every module.
+    Extract(Box<ExtractArgs>),
+    /// Print the file table of every module. Nothing is written to disk.
+    List(ListArgs),
+    /// Print the header of every module, or write the headers to a file. The file table is not read.
+    Info(InfoArgs),
+    /// Write the header and file table of every module to a JSON file.
+    Manifest(OutputArgs),
+    /// Validate modules and print diagnostics.
+    Check(CheckArgs),
+    /// Report how often checksum algorithms reproduce the stored block checksums.
+    ProbeChecksums(ProbeArgs),
+    /// Write the block table of every module to a CSV file.
+    Blocks(OutputArgs),
+    /// Write one row per file of every module to a CSV file.
+    Index(OutputArgs),
+    /// Write the parent/resource graph of every module to a file, as JSON when the path ends in `.json` and as
+    /// Graphviz DOT otherwise.
+    Graph(OutputArgs),
+    /// Report entries that are duplicated across all walked modules.
+    Duplicates(DuplicatesArgs),
+    /// Extract only the files of a module that were added or changed since an older version of it.
+    Diff(DiffArgs),
+    /// Rebuild a module from its extracted tags and the output of the manifest command.
+    ///
+    /// The header checksum is copied from the manifest unchanged because its algorithm is not known, so a module
+    /// rebuilt from modified tags carries the checksum of the original.
+    Pack(PackArgs),
+    /// Print the JSON Schema of the manifest command's output.
+    #[cfg(feature = "schemars")]
+    JsonSchema,
+}
+
+#[derive(Args, Debug)]
+struct ExtractArgs {
     /// Path to save tags to.
+    #[arg(short, long, required_unless_present_any = ["stdout", "output_archive"])]
+    save_path: Option<String>,
+    /// Write every file into this zip archive instead of the save path, at the path it would have had below
+    /// the save path. Files are added one at a time as they are extracted.
+    #[arg(long, value_name = "PATH", conflicts_with = "save_path")]
+    output_archive: Option<PathBuf>,
+    /// How files are compressed inside the --output-archive.
+    #[arg(long, value_enum, default_value_t = ArchiveCompression::Deflate)]
+    archive_compression: ArchiveCompression,
+    /// Write every module into the save path directly instead of a per-module subdirectory.
+    #[arg(long)]
+    merge: bool,
+    /// Name each module's subdirectory after the module's path relative to the module path instead of only its
+    /// file stem, so modules with the same name in different folders stay apart.
+    #[arg(long, conflicts_with_all = ["merge", "flat"])]
+    group_by_module: bool,
+    /// Append the lowercased group tag as an extension to every written file, e.g. `.bitm`.
+    #[arg(long)]
+    append_group_ext: bool,
+    /// Write every file directly into the save path, named after its hex asset id (or global tag id when it
+    /// has none) and group tag, e.g. `00000000deadbeef.bitm`. Colliding names get a numeric suffix.
+    #[arg(long)]
+    flat: bool,
+    /// What to do when several files map to the same output path. A warning is printed either way; --flat
+    /// always appends a suffix, and so does --output-archive instead of overwriting.
+    #[arg(long, value_enum, default_value_t = OnCollision::Overwrite)]
+    on_collision: OnCollision,
+    /// Extract files in the order of this key instead of the module's table order. Modules are always visited
+    /// in file name order.
+    #[arg(long, value_enum, value_name = "KEY")]
+    sort: Option<EntryOrder>,
+    /// Log modules that fail to dump and carry on with the rest, exiting with an error at the end. Modules
+    /// dumped with more than one job always do this.
+    #[arg(long)]
+    continue_on_error: bool,
+    /// Write a hexdump of every file that fails to extract into this directory.
+    #[arg(long, value_name = "DIR")]
+    debug_dump: Option<String>,
+    /// Skip writing files whose content was already written earlier in the run, even from another module.
+    /// Skipped files are listed in `dedup_manifest.json` in the save path. Keeps a hash of every written file
+    /// in memory.
+    #[arg(long)]
+    global_dedup: bool,
+    /// Cache the parsed tables of every module in this directory and reuse them on later runs while the module's
+    /// header checksum and size are unchanged.
+    #[arg(long, value_name = "DIR")]
+    index_cache: Option<PathBuf>,
+    /// Use synthetic `<index>_<group>` names when a module's name table cannot be read at all.
+    #[arg(long)]
+    lenient_names: bool,
+    /// Read modules as big-endian, as some console variants are, instead of detecting the byte order from the
+    /// module header.
+    #[arg(long)]
+    big_endian: bool,
+    /// Treat suspicious structure, such as block compressed flags other than 0 or 1 or out of range resource
+    /// indices, as errors.
+    #[arg(long)]
+    strict: bool,
+    /// Write a manifest of each module's entries and the paths they were written to into its output directory,
+    /// as `_manifest.json` (or `<module>_manifest.json` with --merge).
+    #[arg(long)]
+    output_manifest_per_module: bool,
+    /// Hash every extracted file and include the hash in the per-module manifest. The number of hashes that
+    /// match the files' `asset_checksum` is logged per module.
+    #[arg(long, value_enum, value_name = "ALGORITHM")]
+    hash: Option<HashAlgorithm>,
+    /// Compute a CRC32 of every file while it is written and include it in the per-module manifest as `crc32`.
+    /// With --split-sections it covers all sections of the tag.
+    #[arg(long, requires = "output_manifest_per_module")]
+    crc: bool,
+    /// Check every decompressed block of Forge modules against its stored checksum with this algorithm and fail
+    /// on the first mismatch. The algorithm the game uses is not known yet, see the probe-checksums command.
+    #[arg(long, value_name = "ALGORITHM", value_parser = parse_checksum_algorithm)]
+    verify: Option<String>,
+    /// Also write the resource section of every file that has one to a sibling `<name>.resource` file. The
+    /// file itself still holds its whole data.
+    #[arg(long, conflicts_with = "raw")]
+    write_resources: bool,
+    /// Write the header, tag and resource sections of every file to separate `<name>.header`, `<name>.tag` and
+    /// `<name>.resource` files instead of one file. Empty sections are not written.
+    #[arg(long, conflicts_with_all = ["raw", "write_resources"])]
+    split_sections: bool,
+    /// Write every file as it is stored in the module instead of decompressing it. Files split into blocks are
+    /// written as the stored data of their blocks back to back.
+    #[arg(long, conflicts_with = "verify")]
+    raw: bool,
+    /// Number of modules extracted concurrently, 0 uses one thread per CPU.
+    /// With more than one job every module is attempted and failures are reported at the end.
+    #[arg(long, default_value_t = 1)]
+    jobs: usize,
+    /// Number of times a read failing with a transient IO error is retried during extraction.
+    #[arg(long, default_value_t = 0)]
+    io_retries: u32,
+    /// Delay in milliseconds before the first retry, doubled for each further retry.
+    #[arg(long, default_value_t = 100, value_name = "MS")]
+    io_retry_backoff: u64,
+    /// Memory-map modules instead of reading them through a buffered reader, falling back to buffered reads
+    /// when a module cannot be mapped.
+    #[arg(long)]
+    mmap: bool,
+    /// Only extract files whose uncompressed size is at least this large (e.g. 512, 64KiB, 10MiB).
+    #[arg(long, value_parser = parse_size)]
+    min_size: Option<u64>,
+    /// Only extract files whose uncompressed size is at most this large (e.g. 512, 64KiB, 10MiB).
+    #[arg(long, value_parser = parse_size)]
+    max_size: Option<u64>,
+    /// Fail files that declare a larger uncompressed size than this instead of allocating a buffer for them,
+    /// which guards against decompression bombs in untrusted modules. Unlike --max-size, which skips files, this
+    /// is an error.
+    #[arg(long, value_parser = parse_size, default_value = "1GiB", value_name = "BYTES")]
+    max_uncompressed_size: u64,
+    /// Skip files with an uncompressed size of zero. When set to false they are written as zero-byte files.
+    #[arg(long, default_value_t = true, action = ArgAction::Set)]
+    exclude_empty: bool,
+    /// Write files with an uncompressed size of zero as zero-byte files, so every path of the module exists in
+    /// the output. Same as --exclude-empty false.
+    #[arg(long, conflicts_with = "exclude_empty")]
+    include_empty: bool,
+    /// Abort when an entry has flag bits that are not known to the dumper.
+    #[arg(long)]
+    fail_on_unknown_flags: bool,
+    /// Skip entries with unknown flag bits instead of aborting.
+    #[arg(long, requires = "fail_on_unknown_flags")]
+    skip_unknown_flags: bool,
+    /// Only extract files whose name matches this regular expression.
+    /// Applied after the size filters; a file must pass every filter to be extracted.
+    #[arg(long, value_parser = Regex::new)]
+    name_regex: Option<Regex>,
+    /// Only extract files whose name matches this glob, e.g. `objects/vehicles/**`. `*` does not cross `/`,
+    /// `**` does.
+    #[arg(long, value_name = "GLOB", value_parser = parse_glob)]
+    name_pattern: Option<GlobMatcher>,
+    /// Only extract the file with exactly this name.
+    #[arg(long, value_name = "NAME")]
+    extract_name: Option<String>,
+    /// Only extract the file whose global tag id or asset id is this hex value, e.g. `0xdeadbeef`.
+    #[arg(long, value_name = "HEX", value_parser = parse_hex_id)]
+    extract_id: Option<u64>,
+    /// Write the file selected by --extract-name to stdout instead of the save path. Only the first match
+    /// across all modules is written.
+    #[arg(long, requires = "extract_name")]
+    stdout: bool,
+    /// Stop after this many files have been written, across all modules. Combine with --sort for a stable
+    /// sample.
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+    /// Skip resource entries, i.e. entries that have a parent file.
+    #[arg(long)]
+    skip_resources: bool,
+    /// Only extract files of this group tag (e.g. bitm or "mat ") or tag group name (e.g. bitmap or material).
+    /// Repeatable; group tags are case-sensitive, trailing spaces are ignored.
+    #[arg(long, visible_alias = "filter-kind", value_name = "FOURCC", value_parser = parse_group_tag)]
+    filter_group: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+struct ListArgs {
+    /// List files in the order of this key instead of the module's table order.
+    #[arg(long, value_enum, value_name = "KEY")]
+    sort: Option<EntryOrder>,
+    /// Only list files whose name matches this glob, e.g. `objects/vehicles/**`. `*` does not cross `/`, `**`
+    /// does.
+    #[arg(long, value_name = "GLOB", value_parser = parse_glob)]
+    name_pattern: Option<GlobMatcher>,
+    /// Leave out files with an uncompressed size of zero. How many were left out is printed with the count.
+    #[arg(long)]
+    exclude_empty: bool,
+}
+
+#[derive(Args, Debug)]
+struct InfoArgs {
+    /// Write the headers here instead of printing them.
+    /// A `.json`/`.jsonl` path receives one JSON record per line and module, any other path is a directory
+    /// receiving the raw header bytes of each module as `<module>.header`.
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+    /// Override the --output format that is otherwise picked from the path's extension.
+    #[arg(long, value_enum, requires = "output")]
+    format: Option<HeaderFormat>,
+}
+
+#[derive(Args, Debug)]
+struct OutputArgs {
+    /// File to write to.
+    #[arg(short, long, value_name = "PATH")]
+    output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct CheckArgs {
+    /// Also require the stored blocks of every file to cover its compressed size without gaps.
+    #[arg(long)]
+    contiguous: bool,
+}
+
+#[derive(Args, Debug)]
+struct ProbeArgs {
+    /// Algorithm to probe. Repeatable; one of crc32, xxh32, xxh64, fnv1a64, sum32, sum64 or all.
+    #[arg(long, value_name = "ALGORITHM", required = true)]
+    algorithm: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+struct DuplicatesArgs {
+    /// What to compare when looking for duplicates.
+    #[arg(long, value_enum, default_value = "asset-checksum")]
+    key: DuplicateKey,
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// Module to compare against.
+    #[arg(long)]
+    old: PathBuf,
+    /// Module to extract added and changed files from.
+    #[arg(long)]
+    new: PathBuf,
+    /// Path to save the files to.
     #[arg(short, long)]
-    save_path: String,
+    save_path: PathBuf,
+    /// How files of the two modules are matched.
+    #[arg(long, value_enum, default_value_t = MatchKey::Name)]
+    match_by: MatchKey,
 }
 
-fn read_module(file_name: &Path, save_path: &String) -> Result<()> {
+#[derive(Args, Debug)]
+struct PackArgs {
+    /// Directory holding the module's tags as written by extract, without --flat or --append-group-ext.
+    #[arg(short, long, value_name = "DIR")]
+    input: PathBuf,
+    /// Output of the manifest command for the original module.
+    #[arg(long, value_name = "PATH")]
+    manifest: PathBuf,
+    /// File name of the module to rebuild, when the manifest describes more than one.
+    #[arg(long, value_name = "NAME")]
+    module: Option<String>,
+    /// Module file to write.
+    #[arg(short, long, value_name = "PATH")]
+    output: PathBuf,
+    /// Uncompressed size of the blocks that tags split into blocks are recompressed in (e.g. 64KiB).
+    #[arg(long, value_parser = parse_size, default_value = "128KiB", value_name = "BYTES")]
+    block_size: u64,
+    /// Algorithm to compute the block checksums of Forge modules with, one of crc32, xxh32, xxh64, fnv1a64, sum32
+    /// or sum64. Block checksums are written as zero without it.
+    #[arg(long, value_name = "ALGORITHM")]
+    block_checksum: Option<String>,
+}
+
+/// What to do with a file whose output path was already written during the run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OnCollision {
+    /// Keep the earlier file and skip this one.
+    Skip,
+    /// Append `_1`, `_2`, ... to the file stem.
+    Suffix,
+    /// Replace the earlier file.
+    Overwrite,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ArchiveCompression {
+    Stored,
+    Deflate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum HeaderFormat {
+    Json,
+    Raw,
+}
+
+/// Version shown by `--version`, listing the supported module versions, file flags and compression.
+fn long_version() -> String {
+    let versions: Vec<_> = ModuleVersion::ALL
+        .iter()
+        .map(|version| format!("{} ({})", *version as u32, version.title()))
+        .collect();
+    let flags: Vec<_> = FileFlags::all()
+        .iter_names()
+        .map(|(name, _)| name.to_lowercase())
+        .collect();
+    format!(
+        "{}\nmodule versions: {}\nfile flags: {}\ncompression: zlib",
+        env!("CARGO_PKG_VERSION"),
+        versions.join(", "),
+        flags.join(", ")
+    )
+}
+
+fn parse_glob(value: &str) -> Result<GlobMatcher, String> {
+    GlobBuilder::new(value)
+        .literal_separator(true)
+        .build()
+        .map(|glob| glob.compile_matcher())
+        .map_err(|error| error.to_string())
+}
+
+/// Parses a hex id with an optional `0x` prefix.
+fn parse_hex_id(value: &str) -> Result<u64, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u64::from_str_radix(digits, 16).map_err(|error| format!("invalid hex id {value:?}: {error}"))
+}
+
+fn parse_checksum_algorithm(value: &str) -> Result<String, String> {
+    checksum::candidate_by_name(value).map_err(|error| error.to_string())?;
+    Ok(value.to_string())
+}
+
+/// Parses a tag group name or a group tag of up to four characters, into the group tag without its trailing
+/// spaces.
+fn parse_group_tag(value: &str) -> Result<String, String> {
+    if let Some(fourcc) = groups::fourcc_for_name(value) {
+        return Ok(fourcc.trim_end().to_string());
+    }
+    let tag = value.trim_end();
+    if tag.is_empty() || value.chars().count() > 4 {
+        return Err(format!("group tag must be 1 to 4 characters: {value:?}"));
+    }
+    Ok(tag.to_string())
+}
+
+/// Parses a byte count with an optional unit suffix (`B`, `KB`, `KiB`, `MB`, `MiB`, `GB`, `GiB`).
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size: {value}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "kib" | "k" => 1 << 10,
+        "mb" => 1000 * 1000,
+        "mib" | "m" => 1 << 20,
+        "gb" => 1000 * 1000 * 1000,
+        "gib" | "g" => 1 << 30,
+        _ => return Err(format!("unknown size unit: {unit}")),
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("size too large: {value}"))
+}
+
+/// Returns the directory a module is extracted into, relative to the save path.
+///
+/// This is the module's directory name, prefixed with the folders between the module path and the module
+/// when --group-by-module is set.
+fn module_output_directory(
+    arguments: &ExtractArgs,
+    module_path: &Path,
+    file_name: &Path,
+) -> PathBuf {
+    let mut directory = PathBuf::new();
+    if arguments.group_by_module {
+        let relative = file_name.strip_prefix(module_path).unwrap_or(file_name);
+        if let Some(parent) = relative
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            directory = sanitize_path(&parent.to_string_lossy());
+        }
+    }
+    directory.join(module_directory_name(file_name))
+}
+
+/// Returns the directory name used for a module's output, derived from its file stem.
+fn module_directory_name(file_name: &Path) -> String {
+    let stem = file_name
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    sanitize_component(stem.trim_start_matches(['.', ' ']))
+}
+
+/// Returns `path` with `.<suffix>` appended to its file name, keeping any extension it already has.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Returns whether file `index` is the file --extract-id `id` looks up, by global tag id or asset id.
+fn matches_id(module: &H5Module, index: usize, id: u64) -> bool {
+    let by_tag_id = u32::try_from(id)
+        .ok()
+        .and_then(|id| module.find_by_tag_id(id as i32));
+    by_tag_id == Some(index) || module.find_by_asset_id_index(id as i64) == Some(index)
+}
+
+/// Returns the indices of `module`'s files in the order selected with --sort, table order by default.
+fn entry_order(module: &H5Module, order: Option<EntryOrder>) -> Vec<usize> {
+    match order {
+        Some(order) => module.sorted_indices(order),
+        None => (0..module.files.len()).collect(),
+    }
+}
+
+fn list_module(file_name: &Path, arguments: &ListArgs) -> Result<()> {
     let file = File::open(file_name)?;
     let mut reader = BufReader::new(file);
-    let mut module = H5Module::default();
+    let module = H5Module::from_reader(&mut reader)?;
+    let title = module
+        .title()
+        .map_or("Unknown title".to_string(), |title| title.to_string());
+    println!("{title}, build {}", module.header.build_string());
+    let mut listed = 0;
+    let mut empty = 0;
+    for index in entry_order(&module, arguments.sort) {
+        let file = &module.files[index];
+        if arguments
+            .name_pattern
+            .as_ref()
+            .is_some_and(|pattern| !pattern.is_match(&file.name))
+        {
+            continue;
+        }
+        if arguments.exclude_empty && file.total_uncompressed_size == 0 {
+            empty += 1;
+            continue;
+        }
+        listed += 1;
+        let mut kind = Vec::new();
+        if file.flags.contains(FileFlags::COMPRESSED) {
+            kind.push("compressed");
+        }
+        if file.flags.contains(FileFlags::HAS_BLOCKS) {
+            kind.push("blocks");
+        }
+        if kind.is_empty() {
+            kind.push("-");
+        }
+        println!(
+            "{:4} {:22} {:>12} {:>12} {:17} {}",
+            file.group_tag,
+            groups::name_for_fourcc(&file.group_tag).unwrap_or("-"),
+            file.total_compressed_size,
+            file.total_uncompressed_size,
+            kind.join(","),
+            file.name
+        );
+    }
+    let excluded = if arguments.exclude_empty {
+        format!(", {empty} empty file(s) excluded")
+    } else {
+        String::new()
+    };
+    if listed == module.files.len() {
+        println!("{listed} file(s){excluded}");
+    } else {
+        println!("{listed} of {} file(s){excluded}", module.files.len());
+    }
+    Ok(())
+}
 
-    module.read(&mut reader)?;
-    for file in module.files {
-        let file_p = Path::new("..")
-            .join(save_path)
-            .join(file.name.replace(":", "_").replace("*", "_"));
+fn check_module(file_name: &Path, contiguous: bool) -> Result<()> {
+    let file = File::open(file_name)?;
+    let mut reader = BufReader::new(file);
+    let module = H5Module::from_reader(&mut reader)?;
+    let diagnostics = validate::validate(&module);
+    for diagnostic in &diagnostics {
+        println!(
+            "[{}] {} ({}): {}",
+            diagnostic.severity,
+            diagnostic.file_index,
+            module.files[diagnostic.file_index].name,
+            diagnostic.message
+        );
+    }
+    let mut errors = module.check_resource_indices();
+    let layout = if contiguous {
+        module.validate_contiguous_layout()
+    } else {
+        module.validate_layout()
+    };
+    errors.extend(layout.err());
+    for error in &errors {
+        println!("[{}] {error}", validate::Severity::Warning);
+    }
+    println!("{} diagnostic(s)", diagnostics.len() + errors.len());
+    Ok(())
+}
 
-        std::fs::create_dir_all(file_p.parent().unwrap())?;
-        let mut handle = File::create(file_p)?;
-        handle.write_all(&file.data)?;
+fn probe_module(file_name: &Path, algorithms: &[String]) -> Result<()> {
+    let candidates = checksum::candidates_by_name(algorithms)?;
+    let file = File::open(file_name)?;
+    let mut reader = BufReader::new(file);
+    let module = H5Module::from_reader(&mut reader)?;
+    if !module.header.version_kind()?.has_checksums() {
+        println!("Module has no block checksums");
+        return Ok(());
+    }
+    let report = checksum::probe(&module, &mut reader, &candidates)?;
+    let probed = report.probed;
+    println!(
+        "Probed {probed} block(s), skipped {} unreadable block(s)",
+        report.skipped
+    );
+    for result in report.results {
+        println!(
+            "  {:<8} compressed {}/{probed}  uncompressed {}/{probed}",
+            result.name, result.compressed_matches, result.uncompressed_matches
+        );
     }
     Ok(())
 }
 
-fn main() -> Result<()> {
-    let arguments = H5ModuleLoader::parse();
-    for file in WalkDir::new(arguments.module_path)
+fn dump_module_blocks<W: Write>(file_name: &Path, writer: &mut csv::Writer<W>) -> Result<()> {
+    let file = File::open(file_name)?;
+    let mut reader = BufReader::new(file);
+    let module = H5Module::from_reader(&mut reader)?;
+    export::write_block_rows(writer, &file_name.to_string_lossy(), &module)
+}
+
+fn index_module<W: Write>(file_name: &Path, writer: &mut csv::Writer<W>) -> Result<()> {
+    let file = File::open(file_name)?;
+    let mut reader = BufReader::new(file);
+    let module = H5Module::from_reader(&mut reader)?;
+    export::write_file_rows(writer, &file_name.to_string_lossy(), &module)
+}
+
+fn describe_module(file_name: &Path) -> Result<H5Module> {
+    let file = File::open(file_name)?;
+    let mut reader = BufReader::new(file);
+    H5Module::from_reader(&mut reader)
+}
+
+fn scan_module_duplicates(file_name: &Path, scanner: &mut DuplicateScanner) -> Result<()> {
+    let file = File::open(file_name)?;
+    let mut reader = BufReader::new(file);
+    let module = H5Module::from_reader(&mut reader)?;
+    scanner.add_module(&file_name.to_string_lossy(), &module, &mut reader)
+}
+
+fn print_duplicates(scanner: DuplicateScanner) {
+    let duplicates = scanner.into_duplicates();
+    let wasted: u64 = duplicates.iter().map(|group| group.wasted_bytes()).sum();
+    for group in &duplicates {
+        println!(
+            "{:032x}: {} copies of {} bytes, {} bytes redundant",
+            group.key,
+            group.occurrences.len(),
+            group.size,
+            group.wasted_bytes()
+        );
+        for occurrence in &group.occurrences {
+            println!("    {}: {}", occurrence.module, occurrence.name);
+        }
+    }
+    println!(
+        "{} duplicate group(s), {wasted} redundant byte(s)",
+        duplicates.len()
+    );
+}
+
+fn build_tag_graph(file_name: &Path) -> Result<TagGraph> {
+    let file = File::open(file_name)?;
+    let mut reader = BufReader::new(file);
+    let module = H5Module::from_reader(&mut reader)?;
+    Ok(TagGraph::new(&file_name.to_string_lossy(), &module))
+}
+
+fn write_tag_graphs(path: &Path, graphs: &[TagGraph], json: JsonStyle) -> Result<()> {
+    let contents = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        let modules: Vec<_> = graphs.iter().map(TagGraph::to_json).collect();
+        json.to_string(&serde_json::json!({ "modules": modules }))?
+    } else {
+        graph::to_dot(graphs)?
+    };
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Layout of a JSON output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum JsonStyle {
+    Compact,
+    Pretty,
+}
+
+impl JsonStyle {
+    fn to_string<T: Serialize>(self, value: &T) -> Result<String> {
+        Ok(match self {
+            JsonStyle::Compact => serde_json::to_string(value)?,
+            JsonStyle::Pretty => serde_json::to_string_pretty(value)?,
+        })
+    }
+
+    fn to_writer<W: Write, T: Serialize>(self, writer: W, value: &T) -> Result<()> {
+        match self {
+            JsonStyle::Compact => serde_json::to_writer(writer, value)?,
+            JsonStyle::Pretty => serde_json::to_writer_pretty(writer, value)?,
+        }
+        Ok(())
+    }
+}
+
+/// State shared by every module extracted during a run.
+struct ExtractionState {
+    module_path: PathBuf,
+    dedup: Option<Mutex<ContentDeduplicator>>,
+    verify: Option<Box<dyn ChecksumCandidate>>,
+    output: Output,
+    output_paths: Mutex<UniquePaths>,
+    /// Number of files written so far, or claimed by a module about to write them, for --limit.
+    written: AtomicUsize,
+    progress: Progress,
+    /// Style of the per-module and dedup manifests.
+    json: JsonStyle,
+}
+
+/// Where extracted files end up: loose files on disk, or entries of a zip archive.
+enum Output {
+    Directory,
+    Archive(Box<Mutex<ZipWriter<BufWriter<File>>>>, SimpleFileOptions),
+}
+
+impl Output {
+    fn new(arguments: &ExtractArgs) -> Result<Self> {
+        let Some(path) = &arguments.output_archive else {
+            return Ok(Output::Directory);
+        };
+        let method = match arguments.archive_compression {
+            ArchiveCompression::Stored => CompressionMethod::Stored,
+            ArchiveCompression::Deflate => CompressionMethod::Deflated,
+        };
+        let writer = ZipWriter::new(BufWriter::new(File::create(path)?));
+        Ok(Output::Archive(
+            Box::new(Mutex::new(writer)),
+            SimpleFileOptions::default().compression_method(method),
+        ))
+    }
+
+    /// Writes `data` to `path`, creating missing directories, or adds it to the archive with `path` as its name.
+    fn write(&self, path: &Path, data: &[u8]) -> Result<()> {
+        self.write_hashed(path, data, None)
+    }
+
+    /// Like `write`, additionally feeding the written bytes into `crc` when given.
+    fn write_hashed(
+        &self,
+        path: &Path,
+        data: &[u8],
+        crc: Option<&mut crc32fast::Hasher>,
+    ) -> Result<()> {
+        self.write_with(path, crc, |out| Ok(out.write_all(data)?))
+    }
+
+    /// Like `write_hashed`, but `write` produces the contents directly into the file or archive entry, so they
+    /// never have to be held in memory as a whole. Returns what `write` returned; when it fails, the partly
+    /// written file or archive entry is removed again.
+    fn write_with<T>(
+        &self,
+        path: &Path,
+        crc: Option<&mut crc32fast::Hasher>,
+        write: impl FnOnce(&mut dyn Write) -> Result<T>,
+    ) -> Result<T> {
+        match self {
+            Output::Directory => {
+                std::fs::create_dir_all(path.parent().unwrap())?;
+                let mut file = BufWriter::new(File::create(path)?);
+                let result = write_through(&mut file, crc, write)
+                    .and_then(|value| Ok(file.flush().map(|_| value)?));
+                if result.is_err() {
+                    drop(file);
+                    let _ = std::fs::remove_file(path);
+                }
+                result
+            }
+            Output::Archive(writer, options) => {
+                let name: Vec<_> = path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect();
+                let mut writer = writer.lock().unwrap();
+                writer.start_file(name.join("/"), *options)?;
+                let result = write_through(&mut *writer, crc, write);
+                if result.is_err() {
+                    writer.abort_file()?;
+                }
+                result
+            }
+        }
+    }
+
+    /// Writes the archive's central directory. Nothing to do for loose files.
+    fn finish(self) -> Result<()> {
+        if let Output::Archive(writer, _) = self {
+            writer.into_inner().unwrap().finish()?.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn write_through<W: Write, T>(
+    mut writer: W,
+    crc: Option<&mut crc32fast::Hasher>,
+    write: impl FnOnce(&mut dyn Write) -> Result<T>,
+) -> Result<T> {
+    match crc {
+        Some(hasher) => write(&mut Crc32Writer::new(writer, hasher)),
+        None => write(&mut writer),
+    }
+}
+
+impl ExtractionState {
+    /// Returns whether --limit files have been written, after which extraction stops.
+    fn limit_reached(&self, arguments: &ExtractArgs) -> bool {
+        arguments
+            .limit
+            .is_some_and(|limit| self.written.load(Ordering::Relaxed) >= limit)
+    }
+}
+
+/// Number of files between progress log lines when stdout is not a terminal.
+const FILE_LOG_INTERVAL: u64 = 1000;
+
+/// Extraction progress, drawn as bars on a terminal and logged as plain lines otherwise.
+///
+/// The bars are drawn by the `MultiProgress` the logger suspends while it writes, see `init_logger`.
+struct Progress {
+    bars: Option<MultiProgress>,
+    modules: ProgressBar,
+}
+
+impl Progress {
+    fn new(module_count: usize, bars: Option<MultiProgress>) -> Self {
+        let Some(bars) = bars else {
+            return Progress {
+                bars: None,
+                modules: ProgressBar::with_draw_target(
+                    Some(module_count as u64),
+                    ProgressDrawTarget::hidden(),
+                ),
+            };
+        };
+        let modules = bars.add(
+            ProgressBar::new(module_count as u64).with_style(
+                ProgressStyle::with_template("{elapsed_precise} [{wide_bar}] {pos}/{len} modules")
+                    .unwrap(),
+            ),
+        );
+        Progress {
+            bars: Some(bars),
+            modules,
+        }
+    }
+
+    /// Adds a bar counting the files of the module at `path`.
+    fn start_module(&self, path: &Path, file_count: usize) -> ProgressBar {
+        match &self.bars {
+            Some(bars) => bars.add(
+                ProgressBar::new(file_count as u64)
+                    .with_style(
+                        ProgressStyle::with_template("  [{bar:40}] {pos}/{len} {msg}").unwrap(),
+                    )
+                    .with_message(module_directory_name(path)),
+            ),
+            None => {
+                ProgressBar::with_draw_target(Some(file_count as u64), ProgressDrawTarget::hidden())
+            }
+        }
+    }
+
+    fn file_done(&self, bar: &ProgressBar) {
+        bar.inc(1);
+        if self.bars.is_none() && bar.position().is_multiple_of(FILE_LOG_INTERVAL) {
+            info!("  {}/{} files", bar.position(), bar.length().unwrap_or(0));
+        }
+    }
+
+    fn finish_module(&self, bar: ProgressBar) {
+        bar.finish_and_clear();
+        self.modules.inc(1);
+        if self.bars.is_none() {
+            info!(
+                "Dumped {}/{} module(s)",
+                self.modules.position(),
+                self.modules.length().unwrap_or(0)
+            );
+        }
+    }
+
+    fn finish(&self) {
+        self.modules.finish_and_clear();
+    }
+}
+
+/// Writes log records to stderr, suspending the progress bars while it does so they don't tear.
+struct Logger {
+    inner: env_logger::Logger,
+    bars: Option<MultiProgress>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        match &self.bars {
+            Some(bars) => bars.suspend(|| self.inner.log(record)),
+            None => self.inner.log(record),
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Installs the logger at the level selected by --quiet/--verbose, or `RUST_LOG` when set.
+///
+/// Returns the `MultiProgress` progress bars have to be drawn on, or `None` when no bars should be drawn.
+fn init_logger(arguments: &H5ModuleLoader) -> Result<Option<MultiProgress>> {
+    let level = match (arguments.quiet, arguments.verbose) {
+        (true, _) => LevelFilter::Warn,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+    let inner = env_logger::Builder::new()
+        .filter_level(level)
+        .parse_env("RUST_LOG")
+        .format(|buf, record| match record.level() {
+            Level::Info => writeln!(buf, "{}", record.args()),
+            Level::Warn => writeln!(buf, "Warning: {}", record.args()),
+            Level::Error => writeln!(buf, "Error: {}", record.args()),
+            level => writeln!(buf, "{level}: {}", record.args()),
+        })
+        .build();
+    let bars = (!arguments.quiet && std::io::stdout().is_terminal())
+        .then(|| MultiProgress::with_draw_target(ProgressDrawTarget::stdout()));
+    log::set_max_level(inner.filter());
+    log::set_boxed_logger(Box::new(Logger {
+        inner,
+        bars: bars.clone(),
+    }))?;
+    Ok(bars)
+}
+
+/// Returns the directory files are written below, or the root of the archive with --output-archive.
+fn save_root(arguments: &ExtractArgs) -> PathBuf {
+    if arguments.output_archive.is_some() {
+        return PathBuf::new();
+    }
+    let save_path = arguments
+        .save_path
+        .as_deref()
+        .expect("--save-path is required when extracting");
+    PathBuf::from(save_path)
+}
+
+fn dump_module_header<W: Write>(
+    file_name: &Path,
+    save_path: &Path,
+    json_writer: Option<&mut W>,
+    style: JsonStyle,
+) -> Result<()> {
+    let header = ModuleHeader::open_header_only(file_name)?;
+    match json_writer {
+        Some(writer) => {
+            style.to_writer(&mut *writer, &export::header_json(file_name, &header))?;
+            writeln!(writer)?;
+        }
+        None => {
+            let mut raw = vec![0u8; header.size()?];
+            File::open(file_name)?.read_exact(&mut raw)?;
+            std::fs::create_dir_all(save_path)?;
+            let raw_path = save_path.join(format!("{}.header", module_directory_name(file_name)));
+            std::fs::write(raw_path, raw)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_module(
+    file_name: &Path,
+    arguments: &ExtractArgs,
+    state: &ExtractionState,
+    pool: &mut BufferPool,
+) -> Result<ExtractionReport> {
+    let file = File::open(file_name)?;
+    if arguments.mmap {
+        // Safety: the mapping is only read, modules being modified while they are dumped is not supported.
+        match unsafe { Mmap::map(&file) } {
+            Ok(map) => {
+                return extract_module(file_name, arguments, state, pool, Cursor::new(map));
+            }
+            Err(error) => warn!(
+                "could not map {}, falling back to buffered reads: {error}",
+                file_name.display()
+            ),
+        }
+    }
+    let retry_policy = RetryPolicy {
+        retries: arguments.io_retries,
+        backoff: Duration::from_millis(arguments.io_retry_backoff),
+    };
+    let reader = BufReader::new(RetryingReader::new(file, retry_policy));
+    extract_module(file_name, arguments, state, pool, reader)
+}
+
+fn extract_module<R: BufReaderExt + Seek>(
+    file_name: &Path,
+    arguments: &ExtractArgs,
+    state: &ExtractionState,
+    pool: &mut BufferPool,
+    mut reader: R,
+) -> Result<ExtractionReport> {
+    let save_root = save_root(arguments);
+    let output_root = if arguments.merge || arguments.flat {
+        save_root.clone()
+    } else {
+        save_root.join(module_output_directory(
+            arguments,
+            &state.module_path,
+            file_name,
+        ))
+    };
+    let started = Instant::now();
+    let cache = arguments.index_cache.as_deref().map(IndexCache::new);
+    let cached = match &cache {
+        Some(cache) => cache.load(file_name)?.filter(|module| {
+            module.lenient_names == arguments.lenient_names
+                && (!arguments.big_endian || module.header.endianness == Endianness::Big)
+        }),
+        None => None,
+    };
+    let mut module = match cached {
+        Some(module) => {
+            debug!(
+                "Loaded the tables of {} from the index cache",
+                file_name.display()
+            );
+            module
+        }
+        None => {
+            let mut module = H5Module::default();
+            module.lenient_names = arguments.lenient_names;
+            if arguments.big_endian {
+                module.endianness = Some(Endianness::Big);
+            }
+            module.read(&mut reader)?;
+            module.read_file_len(&mut reader)?;
+            if let Some(cache) = &cache {
+                cache.store(file_name, &module)?;
+            }
+            module
+        }
+    };
+    module.max_uncompressed_size = Some(arguments.max_uncompressed_size);
+    let parse_time = started.elapsed();
+    if module.synthetic_names {
+        warn!(
+            "the name table of {} could not be read, files are written with synthetic names",
+            file_name.display()
+        );
+    }
+    if module.lossy_strings > 0 {
+        warn!(
+            "{} file(s) of {} have names or group tags with invalid UTF-8, replaced with U+FFFD",
+            module.lossy_strings,
+            file_name.display()
+        );
+    }
+    for (index, block) in module.blocks.iter().enumerate() {
+        if !block.has_valid_compressed_flag() {
+            let error = ModuleError::InvalidCompressedFlag {
+                block: index,
+                value: block.compressed_raw,
+            };
+            if arguments.strict {
+                bail!(error)
+            }
+            warn!("{error}");
+        }
+    }
+    for error in module.check_resource_indices() {
+        if arguments.strict {
+            bail!(error)
+        }
+        warn!("{error}");
+    }
+    let bar = state.progress.start_module(file_name, module.files.len());
+    let mut outcomes = vec![EntryOutcome::default(); module.files.len()];
+    let mut hashes = vec![None; module.files.len()];
+    let mut crcs = vec![None; module.files.len()];
+    let mut checksum_matches = 0;
+    let mut report = ExtractionReport::default();
+    let mut regex_matched = 0;
+    for index in entry_order(&module, arguments.sort) {
+        if state.limit_reached(arguments) {
+            break;
+        }
+        let outcome = &mut outcomes[index];
+        state.progress.file_done(&bar);
+        let size = module.files[index].total_uncompressed_size as u64;
+        let unknown_flags = module.files[index].unknown_flag_bits();
+        if unknown_flags != 0 && arguments.fail_on_unknown_flags {
+            let error = ModuleError::UnknownFileFlags {
+                name: module.files[index].name.clone(),
+                bits: unknown_flags,
+            };
+            if !arguments.skip_unknown_flags {
+                bail!(error)
+            }
+            warn!("skipping: {error}");
+            report.skip("unknown flags");
+            continue;
+        }
+        if size == 0 && arguments.exclude_empty && !arguments.include_empty {
+            report.skip("empty");
+            continue;
+        }
+        if arguments
+            .extract_name
+            .as_ref()
+            .is_some_and(|name| *name != module.files[index].name)
+        {
+            report.skip("name not requested");
+            continue;
+        }
+        if arguments
+            .extract_id
+            .is_some_and(|id| !matches_id(&module, index, id))
+        {
+            report.skip("id not requested");
+            continue;
+        }
+        if arguments.skip_resources && module.files[index].parent_file_index >= 0 {
+            report.skip("resource");
+            continue;
+        }
+        if !arguments.filter_group.is_empty()
+            && !arguments
+                .filter_group
+                .iter()
+                .any(|group| *group == module.files[index].group_tag.trim_end())
+        {
+            report.skip("group filter");
+            continue;
+        }
+        if arguments.min_size.is_some_and(|min| size < min)
+            || arguments.max_size.is_some_and(|max| size > max)
+        {
+            report.skip("size filter");
+            continue;
+        }
+        if let Some(regex) = &arguments.name_regex {
+            if !regex.is_match(&module.files[index].name) {
+                report.skip("name regex");
+                continue;
+            }
+            regex_matched += 1;
+        }
+        if arguments
+            .name_pattern
+            .as_ref()
+            .is_some_and(|pattern| !pattern.is_match(&module.files[index].name))
+        {
+            report.skip("name pattern");
+            continue;
+        }
+
+        // Plain extraction streams every tag into its file, everything else needs the whole tag in memory.
+        let stream = size != 0
+            && !arguments.raw
+            && state.verify.is_none()
+            && state.dedup.is_none()
+            && arguments.hash.is_none()
+            && !arguments.split_sections
+            && !arguments.write_resources;
+        // Empty entries are written as zero-byte files, `extract_tag` would reject them with `EmptyTag`.
+        let result = if size == 0 || stream {
+            Ok(Vec::new())
+        } else if arguments.raw {
+            module.extract_raw(index, &mut reader)
+        } else if let Some(checksum) = &state.verify {
+            module.extract_tag_verified(index as u32, &mut reader, pool, checksum.as_ref())
+        } else {
+            module.extract_tag_with_pool(index as u32, &mut reader, pool)
+        };
+        let data = match result {
+            Ok(data) => data,
+            Err(error) => {
+                extraction_failed(
+                    file_name,
+                    arguments,
+                    &module,
+                    index,
+                    &mut reader,
+                    &mut report,
+                    error,
+                )?;
+                continue;
+            }
+        };
+        let sections = if arguments.split_sections {
+            let ranges: Result<Vec<_>> = TagSection::ALL
+                .iter()
+                .map(|&section| Ok((section, module.section_range(index, section)?)))
+                .collect();
+            match ranges {
+                Ok(ranges) => ranges,
+                Err(error) => {
+                    warn!("skipping {} ({}): {error}", index, module.files[index].name);
+                    pool.give(data);
+                    report.skip("bad section sizes");
+                    continue;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        let file = &module.files[index];
+        let file_p = if arguments.flat {
+            let name = flat_name(file.asset_id, file.global_tag_id, &file.group_tag);
+            state
+                .output_paths
+                .lock()
+                .unwrap()
+                .claim(output_root.join(name))
+        } else {
+            let mut file_p = output_root.join(sanitize_path(&file.name));
+            if arguments.append_group_ext {
+                file_p = with_group_extension(file_p, &file.group_tag);
+            }
+            let mut output_paths = state.output_paths.lock().unwrap();
+            if output_paths.insert(file_p.clone()) {
+                file_p
+            } else {
+                match arguments.on_collision {
+                    OnCollision::Skip => {
+                        warn!(
+                            "skipping {}, {} was already written",
+                            file.name,
+                            file_p.display()
+                        );
+                        pool.give(data);
+                        report.skip("path collision");
+                        continue;
+                    }
+                    OnCollision::Overwrite if arguments.output_archive.is_none() => {
+                        warn!("overwriting {} with {}", file_p.display(), file.name);
+                        file_p
+                    }
+                    // Entries of an archive cannot be replaced, so colliding files get a suffix there.
+                    OnCollision::Suffix | OnCollision::Overwrite => {
+                        let suffixed = output_paths.claim(file_p.clone());
+                        warn!(
+                            "writing {} to {}, {} was already written",
+                            file.name,
+                            suffixed.display(),
+                            file_p.display()
+                        );
+                        suffixed
+                    }
+                }
+            }
+        };
+
+        if let Some(algorithm) = arguments.hash {
+            let digest = algorithm.digest(&data);
+            if algorithm.matches_checksum(&digest, file.asset_checksum) {
+                checksum_matches += 1;
+            }
+            hashes[index] = Some(hash::to_hex(&digest));
+        }
+
+        // The dedup lock is held until the --limit claim so content is only recorded once its file is certain
+        // to be written.
+        let mut dedup = state
+            .dedup
+            .as_ref()
+            .filter(|_| !data.is_empty())
+            .map(|dedup| dedup.lock().unwrap());
+        let relative_path = file_p.strip_prefix(&save_root)?;
+        if let Some(dedup) = &mut dedup {
+            if let Some(original) = dedup.original_of(&data) {
+                let reference = DedupReference {
+                    module: file_name.to_string_lossy().into_owned(),
+                    name: file.name.clone(),
+                    path: relative_path.to_path_buf(),
+                    original: original.to_path_buf(),
+                };
+                *outcome = EntryOutcome::Deduplicated(reference.original.clone());
+                dedup.bytes_saved += data.len() as u64;
+                dedup.references.push(reference);
+                pool.give(data);
+                report.skip("duplicate");
+                continue;
+            }
+        }
+
+        // Claimed only now so files that fail, are filtered out or deduplicated don't count towards --limit.
+        if let Some(limit) = arguments.limit {
+            if state.written.fetch_add(1, Ordering::Relaxed) >= limit {
+                pool.give(data);
+                break;
+            }
+        }
+        if let Some(mut dedup) = dedup {
+            dedup.record(&data, relative_path);
+        }
+        let mut crc = arguments.crc.then(crc32fast::Hasher::new);
+        let mut written = data.len() as u64;
+        if arguments.split_sections {
+            for (section, range) in sections {
+                if !range.is_empty() {
+                    let section_p = with_suffix(&file_p, section.name());
+                    state
+                        .output
+                        .write_hashed(&section_p, &data[range], crc.as_mut())?;
+                    debug!("Wrote {}", section_p.display());
+                }
+            }
+        } else if stream {
+            let result = state.output.write_with(&file_p, crc.as_mut(), |mut out| {
+                module.write_tag_to(index as u32, &mut reader, &mut out)
+            });
+            match result {
+                Ok(size) => written = size,
+                Err(error) => {
+                    if arguments.limit.is_some() {
+                        state.written.fetch_sub(1, Ordering::Relaxed);
+                    }
+                    extraction_failed(
+                        file_name,
+                        arguments,
+                        &module,
+                        index,
+                        &mut reader,
+                        &mut report,
+                        error,
+                    )?;
+                    continue;
+                }
+            }
+            debug!("Wrote {}", file_p.display());
+        } else {
+            state.output.write_hashed(&file_p, &data, crc.as_mut())?;
+            debug!("Wrote {}", file_p.display());
+        }
+        crcs[index] = crc.map(crc32fast::Hasher::finalize);
+        report.write(
+            written,
+            !file.flags.contains(FileFlags::RAW_FILE)
+                && file
+                    .flags
+                    .intersects(FileFlags::COMPRESSED | FileFlags::HAS_BLOCKS),
+        );
+        if arguments.write_resources {
+            match module.resource_range(index) {
+                Ok(resource) if resource.is_empty() => {}
+                Ok(resource) => {
+                    let resource_p = with_suffix(&file_p, "resource");
+                    state.output.write(&resource_p, &data[resource])?;
+                    debug!("Wrote {}", resource_p.display());
+                }
+                Err(error) => {
+                    warn!("not writing the resource of {}: {error}", file.name);
+                    report.skip("bad section sizes");
+                }
+            }
+        }
+        pool.give(data);
+        *outcome = EntryOutcome::Written(file_p.strip_prefix(&output_root)?.to_path_buf());
+    }
+    state.progress.finish_module(bar);
+    if arguments.output_manifest_per_module {
+        let manifest_name = if arguments.merge || arguments.flat {
+            format!("{}_manifest.json", module_directory_name(file_name))
+        } else {
+            "_manifest.json".to_string()
+        };
+        let manifest = manifest::extraction_manifest(file_name, &module, &outcomes, &hashes, &crcs);
+        state.output.write(
+            &output_root.join(manifest_name),
+            state.json.to_string(&manifest)?.as_bytes(),
+        )?;
+    }
+    for (reason, count) in &report.skip_reasons {
+        info!("Skipped {count} file(s): {reason}");
+    }
+    if arguments.name_regex.is_some() {
+        info!("Name regex matched {regex_matched} file(s)");
+    }
+    if let Some(algorithm) = arguments.hash {
+        let hashed = hashes.iter().flatten().count();
+        info!("{checksum_matches} of {hashed} {algorithm:?} hash(es) match asset_checksum");
+    }
+    let extract_time = started.elapsed() - parse_time;
+    info!(
+        "module={} parse_ms={:.1} extract_ms={:.1} files={} bytes={} mb_per_s={:.1}",
+        file_name.display(),
+        parse_time.as_secs_f64() * 1000.0,
+        extract_time.as_secs_f64() * 1000.0,
+        report.extracted,
+        report.bytes_written,
+        megabytes_per_second(report.bytes_written, extract_time)
+    );
+    Ok(report)
+}
+
+/// Handles file `index` of `module` failing to extract. Errors that only concern the file are logged and counted
+/// in `report`, anything else is returned, after writing a failure dump when --debug-dump is set.
+fn extraction_failed<R: Read + Seek>(
+    file_name: &Path,
+    arguments: &ExtractArgs,
+    module: &H5Module,
+    index: usize,
+    reader: &mut R,
+    report: &mut ExtractionReport,
+    error: anyhow::Error,
+) -> Result<()> {
+    if matches!(
+        error.downcast_ref(),
+        Some(ModuleError::EmptyTag | ModuleError::NonCompressedSingleTag)
+    ) {
+        warn!("skipping {} ({}): {error}", index, module.files[index].name);
+        report.fail(&module.files[index].name, &error);
+        return Ok(());
+    }
+    if let Some(dir) = &arguments.debug_dump {
+        let dump_path = debug_dump::write_failure_dump(
+            Path::new(dir),
+            file_name,
+            module,
+            index,
+            reader,
+            &error,
+        )?;
+        info!("Wrote failure dump: {}", dump_path.display());
+    }
+    Err(error)
+}
+
+/// Returns the throughput of `bytes` written in `time`, in decimal megabytes per second.
+fn megabytes_per_second(bytes: u64, time: Duration) -> f64 {
+    if time.is_zero() {
+        return 0.0;
+    }
+    bytes as f64 / 1_000_000.0 / time.as_secs_f64()
+}
+
+/// Extracts `paths` on a pool of `--jobs` threads, each with its own reader and buffer pool.
+///
+/// Every module is attempted even when others fail; the failures are reported together afterwards.
+fn read_modules_parallel(
+    paths: &[PathBuf],
+    arguments: &ExtractArgs,
+    state: &ExtractionState,
+) -> Result<(ExtractionReport, usize)> {
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(arguments.jobs)
+        .build()?;
+    let results: Vec<_> = thread_pool.install(|| {
+        paths
+            .par_iter()
+            .map_init(BufferPool::default, |pool, path| {
+                info!("Dumping module: {}", path.display());
+                read_module(path, arguments, state, pool).map_err(|error| (path, error))
+            })
+            .collect()
+    });
+    let mut report = ExtractionReport::default();
+    let mut failures = Vec::new();
+    for result in results {
+        match result {
+            Ok(module_report) => report.merge(module_report),
+            Err(failure) => failures.push(failure),
+        }
+    }
+    for (path, error) in &failures {
+        error!("failed to dump {}: {error:#}", path.display());
+    }
+    Ok((report, failures.len()))
+}
+
+/// Returns every `.module` file below `module_path`, or `module_path` itself when it names a file, whatever its
+/// extension.
+fn module_files(module_path: &str) -> Vec<PathBuf> {
+    if Path::new(module_path).is_file() {
+        return vec![PathBuf::from(module_path)];
+    }
+    WalkDir::new(module_path)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(|e| e.ok())
+        .filter(|entry| entry.path().to_string_lossy().ends_with("module"))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Writes the decompressed bytes, or with `raw` the stored bytes, of the first file named `name` to stdout,
+/// logging nothing there.
+fn stream_tag(module_path: &str, name: &str, raw: bool) -> Result<()> {
+    for path in module_files(module_path) {
+        let mut reader = BufReader::new(File::open(&path)?);
+        let module = H5Module::from_reader(&mut reader)?;
+        let Some(index) = module.find_by_name(name) else {
+            continue;
+        };
+        let data = if module.files[index].total_uncompressed_size == 0 {
+            Vec::new()
+        } else if raw {
+            module.extract_raw(index, &mut reader)?
+        } else {
+            module.extract_tag(index as u32, &mut reader)?
+        };
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&data)?;
+        stdout.flush()?;
+        return Ok(());
+    }
+    bail!("No file named '{name}' found in {module_path}")
+}
+
+/// Rebuilds the module `arguments.manifest` describes from the tags below `arguments.input` and writes it to
+/// `arguments.output`.
+fn pack_module(arguments: &PackArgs) -> Result<()> {
+    let manifest = PackManifest::from_json(
+        &std::fs::read_to_string(&arguments.manifest)?,
+        arguments.module.as_deref(),
+    )?;
+    let block_checksum = arguments
+        .block_checksum
+        .as_deref()
+        .map(checksum::candidate_by_name)
+        .transpose()?;
+    let options = PackOptions {
+        block_size: usize::try_from(arguments.block_size)?,
+        block_checksum: block_checksum.as_deref(),
+    };
+    let mut out = BufWriter::new(File::create(&arguments.output)?);
+    pack::pack(&manifest, &arguments.input, &options, &mut out)?;
+    out.flush()?;
+    info!(
+        "Packed {} file(s) into {}",
+        manifest.files.len(),
+        arguments.output.display()
+    );
+    Ok(())
+}
+
+/// Writes the files of `arguments.new` that were added or changed since `arguments.old` below
+/// `arguments.save_path` and prints how many there were, unless `quiet`.
+fn diff_modules(arguments: &DiffArgs, quiet: bool) -> Result<()> {
+    let old = H5Module::from_reader(&mut BufReader::new(File::open(&arguments.old)?))?;
+    let mut reader = BufReader::new(File::open(&arguments.new)?);
+    let new = H5Module::from_reader(&mut reader)?;
+
+    let changes = diff::changed_entries(&old, &new, arguments.match_by);
+    for &(index, change) in &changes {
+        let file = &new.files[index];
+        info!("{change:?}: {}", file.name);
+        let data = if file.total_uncompressed_size == 0 {
+            Vec::new()
+        } else {
+            new.extract_tag(index as u32, &mut reader)?
+        };
+        let path = arguments.save_path.join(sanitize_path(&file.name));
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, data)?;
+    }
+    let added = changes
+        .iter()
+        .filter(|(_, change)| *change == Change::Added)
+        .count();
+    if !quiet {
+        println!(
+            "{added} added, {} modified of {} file(s)",
+            changes.len() - added,
+            new.files.len()
+        );
+    }
+    Ok(())
+}
+
+/// Extracts every module below `module_path` and prints a summary of the run, unless `quiet`.
+fn extract(
+    module_path: &str,
+    arguments: &ExtractArgs,
+    json: JsonStyle,
+    quiet: bool,
+    bars: Option<MultiProgress>,
+) -> Result<()> {
+    if arguments.stdout {
+        let name = arguments.extract_name.as_deref().unwrap();
+        return stream_tag(module_path, name, arguments.raw);
+    }
+    let paths = module_files(module_path);
+    let state = ExtractionState {
+        module_path: PathBuf::from(module_path),
+        dedup: arguments
+            .global_dedup
+            .then(|| Mutex::new(ContentDeduplicator::default())),
+        verify: arguments
+            .verify
+            .as_deref()
+            .map(checksum::candidate_by_name)
+            .transpose()?,
+        output: Output::new(arguments)?,
+        output_paths: Mutex::default(),
+        written: AtomicUsize::new(0),
+        progress: Progress::new(paths.len(), bars),
+        json,
+    };
+    let mut report = ExtractionReport::default();
+    let mut failed = 0;
+    if arguments.jobs != 1 {
+        (report, failed) = read_modules_parallel(&paths, arguments, &state)?;
+    } else {
+        let mut pool = BufferPool::default();
+        for path in &paths {
+            info!("Dumping module: {}", path.display());
+            match read_module(path, arguments, &state, &mut pool) {
+                Ok(module_report) => report.merge(module_report),
+                Err(error) if arguments.continue_on_error => {
+                    error!("failed to dump {}: {error:#}", path.display());
+                    failed += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+    state.progress.finish();
+    if state.limit_reached(arguments) {
+        info!("Stopped after --limit {} file(s)", arguments.limit.unwrap());
+    }
+    if !quiet {
+        println!("{report}");
+    }
+    if let Some(dedup) = state.dedup {
+        let dedup = dedup.into_inner().unwrap();
+        let manifest_path = save_root(arguments).join("dedup_manifest.json");
+        state.output.write(
+            &manifest_path,
+            state.json.to_string(&dedup.to_json())?.as_bytes(),
+        )?;
+        if !quiet {
+            println!(
+                "Deduplicated {} file(s), {} byte(s) saved",
+                dedup.references.len(),
+                dedup.bytes_saved
+            );
+        }
+    }
+    state.output.finish()?;
+    if failed > 0 {
+        bail!("{failed} of {} module(s) failed to dump", paths.len())
+    }
+    Ok(())
+}
+
+/// Prints the header of every module, or writes them to `arguments.output`.
+fn print_headers(module_path: &str, arguments: &InfoArgs, json_style: JsonStyle) -> Result<()> {
+    let Some(path) = &arguments.output else {
+        for module in module_files(module_path) {
+            println!("Module: {}", module.display());
+            println!("{}", ModuleHeader::open_header_only(&module)?);
+        }
+        return Ok(());
+    };
+    let json = match arguments.format {
+        Some(format) => format == HeaderFormat::Json,
+        None => path
+            .extension()
+            .is_some_and(|extension| extension == "json" || extension == "jsonl"),
+    };
+    let style = if path
+        .extension()
+        .is_some_and(|extension| extension == "jsonl")
     {
-        if file.path().to_str().unwrap().ends_with("module") {
-            println!("Dumping module: {}", file.path().to_str().unwrap());
-            read_module(file.path(), &arguments.save_path)?;
+        JsonStyle::Compact
+    } else {
+        json_style
+    };
+    let mut writer = json
+        .then(|| File::create(path).map(BufWriter::new))
+        .transpose()?;
+    for module in module_files(module_path) {
+        info!("Dumping header: {}", module.display());
+        dump_module_header(&module, path, writer.as_mut(), style)?;
+    }
+    if let Some(writer) = &mut writer {
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Writes `header` followed by one CSV row per module to `path`, produced by `write_rows`.
+fn write_csv(
+    module_path: &str,
+    path: &Path,
+    header: &[&str],
+    write_rows: fn(&Path, &mut csv::Writer<File>) -> Result<()>,
+) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(header)?;
+    for module in module_files(module_path) {
+        info!("Reading module: {}", module.display());
+        write_rows(&module, &mut writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let command = H5ModuleLoader::command().long_version(long_version());
+    let arguments = H5ModuleLoader::from_arg_matches(&command.get_matches())
+        .unwrap_or_else(|error| error.exit());
+    let bars = init_logger(&arguments)?;
+    match &arguments.command {
+        Command::Extract(extract_arguments) => extract(
+            arguments.module_path(),
+            extract_arguments,
+            arguments.json_style(JsonStyle::Pretty),
+            arguments.quiet,
+            bars,
+        )?,
+        Command::List(list) => {
+            for module in module_files(arguments.module_path()) {
+                info!("Listing module: {}", module.display());
+                list_module(&module, list)?;
+            }
+        }
+        Command::Info(info) => print_headers(
+            arguments.module_path(),
+            info,
+            arguments.json_style(JsonStyle::Compact),
+        )?,
+        Command::Manifest(output) => {
+            let mut modules = Vec::new();
+            for module in module_files(arguments.module_path()) {
+                info!("Describing module: {}", module.display());
+                let parsed = describe_module(&module)?;
+                modules.push((module, parsed));
+            }
+            let document = ManifestDocument {
+                schema_version: manifest::SCHEMA_VERSION,
+                modules: modules
+                    .iter()
+                    .map(|(path, module)| ModuleManifest::new(path, module))
+                    .collect(),
+            };
+            std::fs::write(
+                &output.output,
+                arguments
+                    .json_style(JsonStyle::Pretty)
+                    .to_string(&document)?,
+            )?;
+        }
+        #[cfg(feature = "schemars")]
+        Command::JsonSchema => println!(
+            "{}",
+            arguments
+                .json_style(JsonStyle::Pretty)
+                .to_string(&export::manifest_schema())?
+        ),
+        Command::Check(check) => {
+            for module in module_files(arguments.module_path()) {
+                info!("Checking module: {}", module.display());
+                check_module(&module, check.contiguous)?;
+            }
+        }
+        Command::ProbeChecksums(probe) => {
+            for module in module_files(arguments.module_path()) {
+                info!("Probing module: {}", module.display());
+                probe_module(&module, &probe.algorithm)?;
+            }
+        }
+        Command::Blocks(output) => write_csv(
+            arguments.module_path(),
+            &output.output,
+            &export::BLOCK_CSV_HEADER,
+            dump_module_blocks,
+        )?,
+        Command::Index(output) => write_csv(
+            arguments.module_path(),
+            &output.output,
+            &export::FILE_CSV_HEADER,
+            index_module,
+        )?,
+        Command::Graph(output) => {
+            let mut tag_graphs = Vec::new();
+            for module in module_files(arguments.module_path()) {
+                info!("Graphing module: {}", module.display());
+                tag_graphs.push(build_tag_graph(&module)?);
+            }
+            write_tag_graphs(
+                &output.output,
+                &tag_graphs,
+                arguments.json_style(JsonStyle::Compact),
+            )?;
+        }
+        Command::Duplicates(duplicates) => {
+            let mut scanner = DuplicateScanner::new(duplicates.key);
+            for module in module_files(arguments.module_path()) {
+                info!("Scanning module: {}", module.display());
+                scan_module_duplicates(&module, &mut scanner)?;
+            }
+            print_duplicates(scanner);
         }
+        Command::Diff(diff) => diff_modules(diff, arguments.quiet)?,
+        Command::Pack(pack) => pack_module(pack)?,
     }
     Ok(())
 }
@@ -0,0 +1,70 @@
+//! JSON descriptions of module contents.
+
+use crate::loader::H5Module;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Version of the JSON layout of the `manifest` command and the per-module extraction manifests. Bumped when a
+/// field is renamed, removed or changes meaning.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// What happened to a file table entry during extraction.
+#[derive(Clone, Debug, Default)]
+pub enum EntryOutcome {
+    /// Filtered out or otherwise not written.
+    #[default]
+    Skipped,
+    /// Written to this path, relative to the manifest's directory.
+    Written(PathBuf),
+    /// Not written because identical content was already written to this path, relative to the save path.
+    Deduplicated(PathBuf),
+}
+
+/// Describes every entry of `module` together with where, if anywhere, it was written.
+///
+/// `outcomes`, `hashes` and `crcs` are indexed like `module.files`. `hashes` holds the hex hash of every file
+/// that was hashed during extraction, `crcs` the CRC32 of every file computed while it was written.
+pub fn extraction_manifest(
+    module_path: &Path,
+    module: &H5Module,
+    outcomes: &[EntryOutcome],
+    hashes: &[Option<String>],
+    crcs: &[Option<u32>],
+) -> Value {
+    let files: Vec<_> = module
+        .files
+        .iter()
+        .zip(outcomes.iter().zip(hashes).zip(crcs))
+        .enumerate()
+        .map(|(index, (file, ((outcome, hash), crc)))| {
+            let (status, path) = match outcome {
+                EntryOutcome::Skipped => ("skipped", None),
+                EntryOutcome::Written(path) => ("written", Some(path)),
+                EntryOutcome::Deduplicated(path) => ("deduplicated", Some(path)),
+            };
+            json!({
+                "index": index,
+                "name": file.name,
+                "group_tag": file.group_tag,
+                "global_tag_id": file.global_tag_id,
+                "asset_id": file.asset_id,
+                "total_compressed_size": file.total_compressed_size,
+                "total_uncompressed_size": file.total_uncompressed_size,
+                "flags": file.flags.bits(),
+                "status": status,
+                "path": path,
+                "hash": hash,
+                "crc32": crc,
+            })
+        })
+        .collect();
+
+    json!({
+        "schema_version": SCHEMA_VERSION,
+        "module": module_path,
+        "module_id": module.header.module_id,
+        "version": module.header.version,
+        "item_count": module.header.item_count,
+        "files": files,
+    })
+}
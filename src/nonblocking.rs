@@ -0,0 +1,69 @@
+//! Awaitable tag extraction for async services, behind the `tokio` feature.
+//!
+//! Parsing stays synchronous. `H5Module::extract_tag_async` reads a tag's stored bytes through an async reader
+//! and decompresses them on Tokio's blocking pool, so neither the IO nor the inflating blocks a worker thread.
+
+use crate::loader::{FileFlags, H5Module, ModuleError};
+use anyhow::{bail, Result};
+use std::io::{Cursor, SeekFrom};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+impl H5Module {
+    /// Same as `extract_tag`, but reads from an async `reader` and must be awaited inside a Tokio runtime.
+    pub async fn extract_tag_async<R: AsyncRead + AsyncSeek + Unpin>(
+        &self,
+        index: u32,
+        reader: &mut R,
+    ) -> Result<Vec<u8>> {
+        let file = &self.files[index as usize];
+        if file.total_uncompressed_size == 0 {
+            bail!(ModuleError::EmptyTag)
+        }
+        self.check_size_limit(file)?;
+        let tag = self.standalone_tag(index as usize)?;
+        let start = self.data_offset + file.data_offset;
+        let stored_len = tag.file_len.unwrap_or_default();
+        self.check_data_range(file, start, stored_len)?;
+
+        let mut stored = vec![0; stored_len as usize];
+        reader.seek(SeekFrom::Start(start)).await?;
+        reader.read_exact(&mut stored).await?;
+        tokio::task::spawn_blocking(move || tag.extract_tag(0, &mut Cursor::new(stored))).await?
+    }
+
+    /// Returns a module holding only file `index` and its blocks, laid out as if its stored bytes were the whole
+    /// module file, for extraction on another thread.
+    fn standalone_tag(&self, index: usize) -> Result<H5Module> {
+        let mut file = self.files[index].clone();
+        file.data = Vec::new();
+        file.resource_data = Vec::new();
+        file.data_offset = 0;
+        let blocks = if file.flags.contains(FileFlags::HAS_BLOCKS)
+            && !file.flags.contains(FileFlags::RAW_FILE)
+        {
+            file.first_block_index = 0;
+            self.file_blocks(index)?.to_vec()
+        } else {
+            Vec::new()
+        };
+        let stored_len = blocks
+            .iter()
+            .map(|block| {
+                let size = if block.compressed {
+                    block.compressed_size
+                } else {
+                    block.uncompressed_size
+                };
+                block.compressed_offset as u64 + size as u64
+            })
+            .max()
+            .unwrap_or(0)
+            .max(file.total_compressed_size as u64);
+        let mut tag = H5Module::default();
+        tag.files.push(file);
+        tag.blocks = blocks;
+        tag.file_len = Some(stored_len);
+        tag.max_uncompressed_size = self.max_uncompressed_size;
+        Ok(tag)
+    }
+}
@@ -0,0 +1,239 @@
+//! Rebuilding a module from extracted tags, the inverse of extraction.
+//!
+//! `pack` takes the header and file table saved by the `manifest` command and the tags written by `extract`, and
+//! lays out a new module. Compressed entries are recompressed with zlib and the data offsets, string table and
+//! block table are recomputed, everything else is carried over from the manifest. That includes the header
+//! checksum, whose algorithm is unknown.
+
+use crate::checksum::ChecksumCandidate;
+use crate::loader::{FileFlags, ModuleBlock, ModuleFileEntry, ModuleHeader, ModuleVersion};
+use crate::manifest::SCHEMA_VERSION;
+use crate::paths::sanitize_path;
+use anyhow::{anyhow, bail, Result};
+use byteorder::{WriteBytesExt, LE};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Uncompressed size of the blocks `pack` splits `HAS_BLOCKS` entries into by default.
+pub const DEFAULT_BLOCK_SIZE: usize = 0x20000;
+
+/// The tables of a module as saved by the `manifest` command.
+#[derive(Debug, Deserialize)]
+pub struct PackManifest {
+    pub header: ModuleHeader,
+    pub files: Vec<ModuleFileEntry>,
+    #[serde(default)]
+    pub manifest_indices: Vec<i32>,
+    #[serde(default)]
+    pub resource_indices: Vec<i32>,
+}
+
+impl PackManifest {
+    /// Parses the output of the `manifest` command.
+    ///
+    /// When it describes more than one module, `module` selects the one whose path has that file name. Manifests
+    /// written by a newer version of the tool are rejected.
+    pub fn from_json(json: &str, module: Option<&str>) -> Result<PackManifest> {
+        let value: Value = serde_json::from_str(json)?;
+        if let Some(version) = value.get("schema_version").and_then(Value::as_u64) {
+            if version > SCHEMA_VERSION as u64 {
+                bail!("The manifest has schema version {version}, only up to {SCHEMA_VERSION} is supported!")
+            }
+        }
+        let Some(Value::Array(mut modules)) = value.get("modules").cloned() else {
+            return Ok(serde_json::from_value(value)?);
+        };
+        let index = match module {
+            Some(name) => modules
+                .iter()
+                .position(|entry| {
+                    entry["module"]
+                        .as_str()
+                        .and_then(|path| Path::new(path).file_name())
+                        .is_some_and(|file_name| file_name == name)
+                })
+                .ok_or_else(|| {
+                    anyhow!("The manifest does not describe a module named '{name}'!")
+                })?,
+            None if modules.len() == 1 => 0,
+            None => bail!(
+                "The manifest describes {} modules, pick one by its file name!",
+                modules.len()
+            ),
+        };
+        Ok(serde_json::from_value(modules.swap_remove(index))?)
+    }
+}
+
+/// Settings for `pack`.
+pub struct PackOptions<'a> {
+    /// Uncompressed size of the blocks `HAS_BLOCKS` entries are split into. Each section of an entry starts a new
+    /// block, so the per-section block counts stay meaningful.
+    pub block_size: usize,
+    /// Algorithm that computes the block checksums of Forge modules from the uncompressed block, zero is written
+    /// when unset.
+    pub block_checksum: Option<&'a dyn ChecksumCandidate>,
+}
+
+impl Default for PackOptions<'_> {
+    fn default() -> Self {
+        PackOptions {
+            block_size: DEFAULT_BLOCK_SIZE,
+            block_checksum: None,
+        }
+    }
+}
+
+/// Builds a little-endian module from `manifest` and the tags below `input` and writes it to `out`.
+///
+/// Every tag is read from `input` joined with its sanitized name, the layout `extract` writes without
+/// `--flat` or `--append-group-ext`. A missing tag is only accepted for entries that were empty. When a tag's
+/// size changed, its tag section absorbs the difference.
+pub fn pack<W: Write>(
+    manifest: &PackManifest,
+    input: &Path,
+    options: &PackOptions,
+    out: &mut W,
+) -> Result<()> {
+    let version = ModuleVersion::try_from(manifest.header.version)?;
+    if options.block_size == 0 {
+        bail!("The block size must not be zero!")
+    }
+    let mut files = manifest.files.clone();
+    let mut strings = Vec::new();
+    let mut blocks = Vec::new();
+    let mut data = Vec::new();
+
+    for file in &mut files {
+        file.name_offset = strings.len() as u32;
+        strings.extend_from_slice(file.name.as_bytes());
+        strings.push(0);
+
+        let path = input.join(sanitize_path(&file.name));
+        let tag = match std::fs::read(&path) {
+            Ok(tag) => tag,
+            Err(error)
+                if error.kind() == io::ErrorKind::NotFound && file.total_uncompressed_size == 0 =>
+            {
+                Vec::new()
+            }
+            Err(error) => bail!(
+                "Could not read tag '{}' from {}: {error}",
+                file.name,
+                path.display()
+            ),
+        };
+        resize_sections(file, tag.len())?;
+
+        let start = data.len();
+        file.data_offset = start as u64;
+        file.first_block_index = -1;
+        file.block_count = 0;
+        file.header_block_count = 0;
+        file.tag_block_count = 0;
+        file.resource_block_count = 0;
+        let verbatim = file.flags.contains(FileFlags::RAW_FILE)
+            || !file
+                .flags
+                .intersects(FileFlags::COMPRESSED | FileFlags::HAS_BLOCKS);
+        if verbatim || tag.is_empty() {
+            data.extend_from_slice(&tag);
+        } else if file.flags.contains(FileFlags::HAS_BLOCKS) {
+            file.first_block_index = blocks.len() as i32;
+            let sections = [
+                file.uncompressed_header_size as usize,
+                file.uncompressed_tag_size as usize,
+                file.uncompressed_resource_size as usize,
+            ];
+            let mut section_start = 0;
+            let mut block_counts = [0i16; 3];
+            for (section_size, block_count) in sections.into_iter().zip(&mut block_counts) {
+                let section = &tag[section_start..section_start + section_size];
+                for (chunk_index, chunk) in section.chunks(options.block_size).enumerate() {
+                    let compressed = compress(chunk)?;
+                    blocks.push(ModuleBlock {
+                        checksum: options
+                            .block_checksum
+                            .map_or(0, |checksum| checksum.compute(chunk)),
+                        compressed_offset: (data.len() - start) as u32,
+                        compressed_size: compressed.len() as u32,
+                        uncompressed_offset: (section_start + chunk_index * options.block_size)
+                            as u32,
+                        uncompressed_size: chunk.len() as u32,
+                        compressed: true,
+                        compressed_raw: 1,
+                        padding: 0,
+                    });
+                    data.extend_from_slice(&compressed);
+                    *block_count += 1;
+                }
+                section_start += section_size;
+            }
+            file.block_count = blocks.len() as u32 - file.first_block_index as u32;
+            [
+                file.header_block_count,
+                file.tag_block_count,
+                file.resource_block_count,
+            ] = block_counts;
+        } else {
+            data.extend_from_slice(&compress(&tag)?);
+        }
+        file.total_compressed_size = u32::try_from(data.len() - start)?;
+    }
+
+    let header = ModuleHeader {
+        magic: "mohd".to_string(),
+        version: manifest.header.version,
+        module_id: manifest.header.module_id,
+        item_count: files.len() as u32,
+        manifest_count: manifest.manifest_indices.len() as u32,
+        resource_index: manifest.header.resource_index,
+        strings_size: strings.len() as u32,
+        resource_count: manifest.resource_indices.len() as u32,
+        block_count: blocks.len() as u32,
+        build_version: manifest.header.build_version,
+        checksum: manifest.header.checksum,
+        ..Default::default()
+    };
+    header.write::<LE, _>(out)?;
+    for file in &files {
+        file.write::<LE, _>(out)?;
+    }
+    for &index in &manifest.manifest_indices {
+        out.write_i32::<LE>(index)?;
+    }
+    out.write_all(&strings)?;
+    for &index in &manifest.resource_indices {
+        out.write_i32::<LE>(index)?;
+    }
+    for block in &blocks {
+        block.write::<LE, _>(out, version)?;
+    }
+    out.write_all(&data)?;
+    Ok(())
+}
+
+/// Updates the section sizes of `file` for a tag of `size` bytes, keeping the header and resource sections and
+/// giving the tag section whatever remains.
+fn resize_sections(file: &mut ModuleFileEntry, size: usize) -> Result<()> {
+    let fixed = file.uncompressed_header_size as u64 + file.uncompressed_resource_size as u64;
+    if (size as u64) < fixed {
+        bail!(
+            "Tag '{}' is {size} bytes, too small for its {fixed} bytes of header and resource sections!",
+            file.name
+        )
+    }
+    file.total_uncompressed_size = u32::try_from(size)?;
+    file.uncompressed_tag_size = (size as u64 - fixed) as u32;
+    Ok(())
+}
+
+fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
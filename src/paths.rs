@@ -0,0 +1,119 @@
+//! Mapping of module file names onto output paths.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Device names Windows reserves in every directory, with or without an extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Turns a module file name into a relative path that stays inside whatever directory it is joined onto.
+///
+/// Both `/` and `\` separate components. Empty, `.` and `..` components are dropped so neither absolute
+/// names nor parent references can escape the destination, the rest go through `sanitize_component`.
+pub fn sanitize_path(name: &str) -> PathBuf {
+    let path: PathBuf = name
+        .split(['/', '\\'])
+        .filter(|component| !matches!(*component, "" | "." | ".."))
+        .map(sanitize_component)
+        .collect();
+    if path.as_os_str().is_empty() {
+        PathBuf::from("_")
+    } else {
+        path
+    }
+}
+
+/// Makes a single path component valid on Windows as well as Unix.
+///
+/// Reserved and control characters become `_`, trailing dots and spaces are removed and reserved device
+/// names such as `CON` or `nul.txt` get a `_` appended to their stem.
+pub fn sanitize_component(component: &str) -> String {
+    let mut name: String = component
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    name.truncate(name.trim_end_matches(['.', ' ']).len());
+    if name.is_empty() {
+        return "_".to_string();
+    }
+    let stem_len = name.find('.').unwrap_or(name.len());
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(name[..stem_len].trim_end()))
+    {
+        name.insert(stem_len, '_');
+    }
+    name
+}
+
+/// Appends `.` and the lowercased `group_tag` to the file name of `path`.
+///
+/// `path` is returned unchanged when the group tag is blank or the file name already ends in that extension.
+pub fn with_group_extension(path: PathBuf, group_tag: &str) -> PathBuf {
+    let group = group_tag.trim().to_lowercase();
+    if group.is_empty() {
+        return path;
+    }
+    let extension = sanitize_component(&group);
+    let Some(file_name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return path;
+    };
+    let suffix = format!(".{extension}");
+    if file_name.to_lowercase().ends_with(&suffix) {
+        return path;
+    }
+    let file_name = format!("{file_name}{suffix}");
+    path.with_file_name(file_name)
+}
+
+/// Returns the file name of an entry in a flat dump: its hex `asset_id`, or its `global_tag_id` when the entry
+/// has no asset id, followed by the group tag as an extension.
+pub fn flat_name(asset_id: i64, global_tag_id: i32, group_tag: &str) -> PathBuf {
+    let id = if asset_id == -1 {
+        format!("{:08x}", global_tag_id as u32)
+    } else {
+        format!("{:016x}", asset_id as u64)
+    };
+    with_group_extension(PathBuf::from(id), group_tag)
+}
+
+/// Hands out paths that are unique within a run by appending `_1`, `_2`, ... to the stem of taken paths.
+#[derive(Debug, Default)]
+pub struct UniquePaths {
+    taken: HashSet<PathBuf>,
+}
+
+impl UniquePaths {
+    /// Marks `path` as handed out, returning whether it was still free.
+    pub fn insert(&mut self, path: PathBuf) -> bool {
+        self.taken.insert(path)
+    }
+
+    /// Returns `path`, or the first suffixed variant of it that has not been handed out yet.
+    pub fn claim(&mut self, path: PathBuf) -> PathBuf {
+        let mut candidate = path.clone();
+        let mut suffix = 0;
+        while self.taken.contains(&candidate) {
+            suffix += 1;
+            candidate = with_numeric_suffix(&path, suffix);
+        }
+        self.taken.insert(candidate.clone());
+        candidate
+    }
+}
+
+fn with_numeric_suffix(path: &Path, suffix: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{stem}_{suffix}.{}", extension.to_string_lossy()),
+        None => format!("{stem}_{suffix}"),
+    };
+    path.with_file_name(file_name)
+}
@@ -0,0 +1,77 @@
+//! Reusable byte buffers for tag extraction.
+//!
+//! Extracting a tag allocates a buffer per block plus one for the assembled data. Callers that extract many
+//! tags, possibly across many modules, can keep a `BufferPool` alive and hand it to
+//! `H5Module::extract_tag_with_pool` so those allocations are recycled instead of repeated.
+
+/// Capacity in bytes above which `BufferPool::new` pools drop returned buffers instead of keeping them.
+pub const DEFAULT_MAX_CAPACITY: usize = 32 << 20;
+
+/// A bounded pool of `Vec<u8>` buffers.
+#[derive(Debug)]
+pub struct BufferPool {
+    buffers: Vec<Vec<u8>>,
+    max_buffers: usize,
+    max_capacity: usize,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        BufferPool::new(16)
+    }
+}
+
+impl BufferPool {
+    /// Creates a pool that retains at most `max_buffers` returned buffers of up to `DEFAULT_MAX_CAPACITY` bytes.
+    pub fn new(max_buffers: usize) -> Self {
+        BufferPool::with_max_capacity(max_buffers, DEFAULT_MAX_CAPACITY)
+    }
+
+    /// Creates a pool that retains at most `max_buffers` returned buffers, each of at most `max_capacity` bytes,
+    /// so a single huge tag does not keep its allocation alive for the rest of a run.
+    pub fn with_max_capacity(max_buffers: usize, max_capacity: usize) -> Self {
+        BufferPool {
+            buffers: Vec::with_capacity(max_buffers),
+            max_buffers,
+            max_capacity,
+        }
+    }
+
+    /// Returns the number of buffers currently held.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Takes a zero-filled buffer of exactly `len` bytes, reusing a pooled allocation when one is available.
+    ///
+    /// Prefers a buffer whose capacity already fits `len` so it does not need to grow.
+    pub fn take(&mut self, len: usize) -> Vec<u8> {
+        let position = self
+            .buffers
+            .iter()
+            .position(|buffer| buffer.capacity() >= len)
+            .or(self.buffers.len().checked_sub(1));
+        let mut buffer = match position {
+            Some(position) => self.buffers.swap_remove(position),
+            None => Vec::new(),
+        };
+        buffer.clear();
+        buffer.resize(len, 0);
+        buffer
+    }
+
+    /// Returns a buffer to the pool. It is dropped if the pool is already full or its capacity exceeds the
+    /// pool's limit.
+    pub fn give(&mut self, buffer: Vec<u8>) {
+        if self.buffers.len() < self.max_buffers
+            && buffer.capacity() > 0
+            && buffer.capacity() <= self.max_capacity
+        {
+            self.buffers.push(buffer);
+        }
+    }
+}
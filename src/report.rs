@@ -0,0 +1,77 @@
+//! Summary of an extraction run.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Counts of what happened to the entries of one or more modules during extraction.
+#[derive(Debug, Default)]
+pub struct ExtractionReport {
+    /// Files written to disk, including zero-byte files.
+    pub extracted: usize,
+    /// Files that were not written, for any reason.
+    pub skipped: usize,
+    pub bytes_written: u64,
+    /// Written files that were stored compressed, as a single stream or in blocks.
+    pub compressed: usize,
+    /// Written files that were stored uncompressed.
+    pub raw: usize,
+    /// Number of skipped files per reason.
+    pub skip_reasons: BTreeMap<String, usize>,
+    /// Name and error of every file that was skipped because it failed to extract.
+    pub errors: Vec<(String, String)>,
+}
+
+impl ExtractionReport {
+    /// Counts a file that was not written because of `reason`.
+    pub fn skip(&mut self, reason: &str) {
+        self.skipped += 1;
+        *self.skip_reasons.entry(reason.to_string()).or_default() += 1;
+    }
+
+    /// Counts a file that was skipped because extracting it failed with `error`.
+    pub fn fail(&mut self, name: &str, error: &anyhow::Error) {
+        self.skip("failed to extract");
+        self.errors.push((name.to_string(), error.to_string()));
+    }
+
+    /// Counts a written file of `size` bytes.
+    pub fn write(&mut self, size: u64, compressed: bool) {
+        self.extracted += 1;
+        self.bytes_written += size;
+        if compressed {
+            self.compressed += 1;
+        } else {
+            self.raw += 1;
+        }
+    }
+
+    /// Adds the counts of `other` to this report.
+    pub fn merge(&mut self, other: ExtractionReport) {
+        self.extracted += other.extracted;
+        self.skipped += other.skipped;
+        self.bytes_written += other.bytes_written;
+        self.compressed += other.compressed;
+        self.raw += other.raw;
+        for (reason, count) in other.skip_reasons {
+            *self.skip_reasons.entry(reason).or_default() += count;
+        }
+        self.errors.extend(other.errors);
+    }
+}
+
+impl fmt::Display for ExtractionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Extracted {} file(s), {} byte(s) ({} compressed, {} raw); skipped {} file(s)",
+            self.extracted, self.bytes_written, self.compressed, self.raw, self.skipped
+        )?;
+        for (reason, count) in &self.skip_reasons {
+            write!(f, "\n  {reason}: {count}")?;
+        }
+        for (name, error) in &self.errors {
+            write!(f, "\n  error in {name}: {error}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+//! Read-only sanity checks over a parsed module.
+//!
+//! None of these checks stop extraction, they produce `Diagnostic`s that point at data which is likely
+//! misparsed or otherwise suspicious.
+
+use crate::loader::{FileFlags, H5Module};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file_index: usize,
+    pub message: String,
+}
+
+/// Runs every check against `module` and returns the collected diagnostics in file order.
+pub fn validate(module: &H5Module) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_compression_ratios(module, &mut diagnostics);
+    check_unknown_flags(module, &mut diagnostics);
+    check_block_compressed_flags(module, &mut diagnostics);
+    diagnostics.sort_by_key(|diagnostic| diagnostic.file_index);
+    diagnostics
+}
+
+/// Flags entries whose compressed size is not smaller than their uncompressed size.
+///
+/// A compressed size larger than the uncompressed one usually means the fields were read in the wrong
+/// order, while equal sizes on a compressed entry point at incompressible data.
+fn check_compression_ratios(module: &H5Module, diagnostics: &mut Vec<Diagnostic>) {
+    for (index, file) in module.files.iter().enumerate() {
+        let compressed = file.total_compressed_size;
+        let uncompressed = file.total_uncompressed_size;
+        if uncompressed == 0 || compressed < uncompressed {
+            continue;
+        }
+
+        let ratio = compressed as f64 / uncompressed as f64;
+        if compressed > uncompressed {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                file_index: index,
+                message: format!(
+                    "compressed size {compressed} exceeds uncompressed size {uncompressed} (ratio {ratio:.2})"
+                ),
+            });
+        } else if file
+            .flags
+            .intersects(FileFlags::COMPRESSED | FileFlags::HAS_BLOCKS)
+        {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Info,
+                file_index: index,
+                message: format!(
+                    "compressed size equals uncompressed size {uncompressed} (ratio {ratio:.2})"
+                ),
+            });
+        }
+    }
+}
+
+/// Flags entries carrying flag bits that `FileFlags` does not define.
+fn check_unknown_flags(module: &H5Module, diagnostics: &mut Vec<Diagnostic>) {
+    for (index, file) in module.files.iter().enumerate() {
+        let bits = file.unknown_flag_bits();
+        if bits != 0 {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                file_index: index,
+                message: format!("unknown flag bits {bits:#04x}"),
+            });
+        }
+    }
+}
+
+/// Flags blocks whose compressed field is neither 0 nor 1, reported against every file referencing them.
+fn check_block_compressed_flags(module: &H5Module, diagnostics: &mut Vec<Diagnostic>) {
+    for (index, file) in module.files.iter().enumerate() {
+        if file.first_block_index < 0 {
+            continue;
+        }
+        let first = file.first_block_index as usize;
+        let blocks = module
+            .blocks
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(file.block_count as usize);
+        for (block_index, block) in blocks {
+            if !block.has_valid_compressed_flag() {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    file_index: index,
+                    message: format!(
+                        "block {block_index} has compressed flag {:#x}, expected 0 or 1",
+                        block.compressed_raw
+                    ),
+                });
+            }
+        }
+    }
+}
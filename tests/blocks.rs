@@ -0,0 +1,217 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use flate2::read::ZlibDecoder;
+use h5_dumper::decompress::{BlockDecompressor, ZlibDecompressor};
+use h5_dumper::pool::BufferPool;
+use h5_dumper::{H5Module, ModuleError};
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[test]
+fn block_slice_locates_block_data() {
+    let payload: Vec<u8> = (0..64).collect();
+    let bytes = common::build_module(
+        27,
+        &[FixtureFile {
+            name: "objects/blocks.model",
+            group: "mode",
+            payload: &payload,
+            storage: Storage::Blocks(16),
+        }],
+    );
+    let module = H5Module::from_reader(&mut std::io::Cursor::new(&bytes)).unwrap();
+
+    let (block, offset) = module.block_slice(0, 2).unwrap();
+    let start = offset as usize;
+    let compressed = &bytes[start..start + block.compressed_size as usize];
+    let mut block_data = Vec::new();
+    ZlibDecoder::new(compressed)
+        .read_to_end(&mut block_data)
+        .unwrap();
+    assert_eq!(block_data, payload[32..48]);
+
+    assert!(module.block_slice(0, 4).is_none());
+    assert!(module.block_slice(1, 0).is_none());
+}
+
+/// Counts the streams it is handed before passing them on to zlib.
+#[derive(Default)]
+struct CountingDecompressor(AtomicUsize);
+
+impl BlockDecompressor for CountingDecompressor {
+    fn decompress(&self, input: &[u8], output: &mut [u8]) -> anyhow::Result<()> {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        ZlibDecompressor.decompress(input, output)
+    }
+}
+
+#[test]
+fn extraction_uses_the_given_decompressor() {
+    let payload: Vec<u8> = (0..64).collect();
+    let bytes = common::build_module(
+        27,
+        &[FixtureFile {
+            name: "objects/blocks.model",
+            group: "mode",
+            payload: &payload,
+            storage: Storage::Blocks(16),
+        }],
+    );
+    let mut reader = std::io::Cursor::new(bytes);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+
+    let decompressor = CountingDecompressor::default();
+    let data = module
+        .extract_tag_with_decompressor(0, &mut reader, &mut BufferPool::default(), &decompressor)
+        .unwrap();
+    assert_eq!(data, payload);
+    assert_eq!(decompressor.0.load(Ordering::Relaxed), 4);
+}
+
+#[test]
+fn extract_raw_returns_stored_bytes() {
+    let payload: Vec<u8> = (0..64).collect();
+    let bytes = common::build_module(
+        27,
+        &[
+            FixtureFile {
+                name: "objects/blocks.model",
+                group: "mode",
+                payload: &payload,
+                storage: Storage::Blocks(16),
+            },
+            FixtureFile {
+                name: "objects/single.model",
+                group: "mode",
+                payload: &payload,
+                storage: Storage::Compressed,
+            },
+        ],
+    );
+    let mut reader = std::io::Cursor::new(&bytes);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+
+    let raw = module.extract_raw(0, &mut reader).unwrap();
+    let expected: Vec<u8> = payload.chunks(16).flat_map(common::compress).collect();
+    assert_eq!(raw, expected);
+
+    let raw = module.extract_raw(1, &mut reader).unwrap();
+    assert_eq!(raw, common::compress(&payload));
+}
+
+#[test]
+fn validate_layout_finds_overlaps_and_gaps() {
+    let payload = vec![7u8; 64];
+    let bytes = common::build_module(
+        27,
+        &[FixtureFile {
+            name: "objects/blocks.model",
+            group: "mode",
+            payload: &payload,
+            storage: Storage::Blocks(16),
+        }],
+    );
+    let mut module = H5Module::from_reader(&mut std::io::Cursor::new(bytes)).unwrap();
+    module.validate_layout().unwrap();
+    module.validate_contiguous_layout().unwrap();
+
+    module.blocks[3].compressed_offset += 1;
+    module.files[0].total_compressed_size += 1;
+    module.validate_layout().unwrap();
+    let error = module.validate_contiguous_layout().unwrap_err();
+    assert!(matches!(
+        error,
+        ModuleError::BlockLayoutMismatch { block: 3, .. }
+    ));
+
+    module.blocks[2].compressed_offset = module.blocks[1].compressed_offset + 1;
+    let error = module.validate_layout().unwrap_err();
+    assert!(matches!(
+        error,
+        ModuleError::BlockLayoutMismatch { block: 2, .. }
+    ));
+    assert!(error.to_string().contains("inside block 1"));
+
+    module.blocks[2].compressed_offset = module.files[0].total_compressed_size;
+    let error = module.validate_layout().unwrap_err();
+    assert!(error.to_string().contains("past the"));
+
+    module.files[0].first_block_index = 2;
+    assert!(matches!(
+        module.validate_layout(),
+        Err(ModuleError::BlockRangeOutOfBounds { .. })
+    ));
+}
+
+#[test]
+fn module_without_blocks_reads_and_rejects_blocked_entries() {
+    let files = ["objects/first.bitmap", "objects/second.bitmap"].map(|name| FixtureFile {
+        name,
+        group: "bitm",
+        payload: b"single stream",
+        storage: Storage::Compressed,
+    });
+    let mut reader = std::io::Cursor::new(common::build_module(27, &files));
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    assert_eq!(module.header.block_count, 0);
+    assert!(module.blocks.is_empty());
+    assert_eq!(
+        module.extract_tag(1, &mut reader).unwrap(),
+        b"single stream"
+    );
+
+    module.files[0].flags |= h5_dumper::FileFlags::HAS_BLOCKS;
+    module.files[0].first_block_index = 0;
+    module.files[0].block_count = 1;
+    let error = module.extract_tag(0, &mut reader).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::MissingBlockTable(name)) if name == "objects/first.bitmap"
+    ));
+}
+
+#[test]
+fn compressed_flags_other_than_zero_or_one_are_flagged() {
+    let payload = vec![7u8; 64];
+    let files = [FixtureFile {
+        name: "objects/blocks.model",
+        group: "mode",
+        payload: &payload,
+        storage: Storage::Blocks(16),
+    }];
+    let mut bytes = common::build_module(27, &files);
+    let module = H5Module::from_reader(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert!(module
+        .blocks
+        .iter()
+        .all(|block| block.has_valid_compressed_flag()));
+    assert!(h5_dumper::validate::validate(&module).is_empty());
+
+    // Forge blocks are 32 bytes and end the tables, compressed_raw follows the checksum and four sizes.
+    let second_block = module.data_offset as usize - 32 * (module.blocks.len() - 1);
+    bytes[second_block + 24..second_block + 28].copy_from_slice(&2u32.to_le_bytes());
+    let mut reader = std::io::Cursor::new(bytes);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+    let block = &module.blocks[1];
+    assert_eq!(block.compressed_raw, 2);
+    assert!(block.compressed);
+    assert!(!block.has_valid_compressed_flag());
+
+    let diagnostics = h5_dumper::validate::validate(&module);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "block 1 has compressed flag 0x2, expected 0 or 1"
+    );
+    let error = ModuleError::InvalidCompressedFlag {
+        block: 1,
+        value: block.compressed_raw,
+    };
+    assert_eq!(
+        error.to_string(),
+        "Block 1 has compressed flag 2, expected 0 or 1! The block table is likely misaligned."
+    );
+    // Any non-zero flag still reads as compressed, so the tag extracts.
+    assert_eq!(module.extract_tag(0, &mut reader).unwrap(), payload);
+}
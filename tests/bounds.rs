@@ -0,0 +1,223 @@
+mod common;
+
+use common::Storage;
+use h5_dumper::validate::{self, Severity};
+use h5_dumper::{H5Module, ModuleError};
+use std::io::Cursor;
+
+#[test]
+fn corrupt_block_range_is_an_error() {
+    let payload = vec![7u8; 64];
+    let bytes = common::build_module(
+        27,
+        &[common::FixtureFile {
+            name: "objects/blocks.model",
+            group: "mode",
+            payload: &payload,
+            storage: Storage::Blocks(16),
+        }],
+    );
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+
+    for (first, count) in [(-1, 4), (1, 4), (0, u32::MAX)] {
+        module.files[0].first_block_index = first;
+        module.files[0].block_count = count;
+        let error = module.extract_tag(0, &mut reader).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(ModuleError::BlockRangeOutOfBounds { total: 4, .. })
+        ));
+    }
+}
+
+#[test]
+fn blocks_must_tile_the_uncompressed_size() {
+    let payload = vec![7u8; 64];
+    let bytes = common::build_module(
+        27,
+        &[common::FixtureFile {
+            name: "objects/blocks.model",
+            group: "mode",
+            payload: &payload,
+            storage: Storage::Blocks(16),
+        }],
+    );
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    assert_eq!(module.extract_tag(0, &mut reader).unwrap(), payload);
+
+    module.blocks[2].uncompressed_offset = 40;
+    let error = module.extract_tag(0, &mut reader).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::SizeMismatch { size: 64, .. })
+    ));
+
+    module.blocks[2].uncompressed_offset = 32;
+    module.files[0].total_uncompressed_size = 80;
+    let error = module.extract_tag(0, &mut reader).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Blocks of tag 'objects/blocks.model' do not tile its 80 bytes: blocks add up to 64 bytes!"
+    );
+}
+
+#[test]
+fn blocks_listed_out_of_order_still_extract() {
+    let payload: Vec<u8> = (0..64).collect();
+    let bytes = common::build_module(
+        27,
+        &[common::FixtureFile {
+            name: "objects/blocks.model",
+            group: "mode",
+            payload: &payload,
+            storage: Storage::Blocks(16),
+        }],
+    );
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.blocks.reverse();
+    module.blocks.swap(0, 1);
+
+    assert_eq!(module.extract_tag(0, &mut reader).unwrap(), payload);
+}
+
+#[test]
+fn truncated_data_is_an_error() {
+    let payload = b"bitmap data".repeat(8);
+    let mut bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    bytes.truncate(bytes.len() - 4);
+    let file_len = bytes.len() as u64;
+    let mut reader = Cursor::new(bytes);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+
+    let error = module.extract_tag(0, &mut reader).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::DataOutOfBounds { file_len: len, .. }) if *len == file_len
+    ));
+}
+
+#[test]
+fn stream_shorter_than_uncompressed_size_is_an_error() {
+    let payload = b"bitmap data".repeat(8);
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.files[0].total_uncompressed_size += 1;
+
+    let error = module.extract_tag(0, &mut reader).unwrap_err();
+    let expected = payload.len() as u64 + 1;
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::DecompressedSizeMismatch { expected: size, actual })
+            if *size == expected && *actual == payload.len() as u64
+    ));
+}
+
+#[test]
+fn file_shorter_than_magic_is_not_a_module() {
+    for bytes in [&b""[..], b"mo"] {
+        let error = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(ModuleError::NotAModule { size }) if *size == bytes.len()
+        ));
+    }
+}
+
+#[test]
+fn out_of_range_resource_indices_are_reported() {
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", b"bitmap data");
+    let mut module = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap();
+    assert!(module.check_resource_indices().is_empty());
+    assert!(module.file_resources(0).unwrap().is_empty());
+
+    module.header.resource_index = 1;
+    module.resource_indices = vec![0, 5];
+    module.files[0].first_resource_index = 1;
+    module.files[0].resource_count = 2;
+    let error = module.file_resources(0).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::ResourceIndexOutOfBounds {
+            index: 2,
+            total: 2,
+            ..
+        })
+    ));
+    let errors = module.check_resource_indices();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        errors[0].to_string(),
+        "The module header references index 1 of the file table but it only has 1 entries!"
+    );
+
+    module.files[0].resource_count = 1;
+    let errors = module.check_resource_indices();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        errors[1].to_string(),
+        "Resource 0 of tag 'objects/rifle.bitmap' references index 5 of the file table but it only has 1 entries!"
+    );
+}
+
+#[test]
+fn uncompressed_size_over_the_limit_is_an_error() {
+    let payload = b"bitmap data".repeat(8);
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.max_uncompressed_size = Some(payload.len() as u64);
+    assert_eq!(module.extract_tag(0, &mut reader).unwrap(), payload);
+
+    module.files[0].total_uncompressed_size = u32::MAX;
+    let error = module.extract_tag(0, &mut reader).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::SizeLimitExceeded { size: u32::MAX, limit, .. }) if *limit == payload.len() as u64
+    ));
+}
+
+#[test]
+fn swapped_sizes_are_flagged_by_validate() {
+    let payload = b"bitmap data".repeat(8);
+    let mut bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let module = H5Module::from_reader(&mut Cursor::new(&bytes)).unwrap();
+    assert!(validate::validate(&module).is_empty());
+
+    // Swap total_compressed_size and total_uncompressed_size of the only entry, right after the 56 byte header.
+    let sizes = 56 + 32..56 + 40;
+    bytes[sizes.clone()].rotate_left(4);
+    let module = H5Module::from_reader(&mut Cursor::new(&bytes)).unwrap();
+    let file = &module.files[0];
+    assert_eq!(file.total_compressed_size as usize, payload.len());
+    assert!(file.total_uncompressed_size < file.total_compressed_size);
+
+    let diagnostics = validate::validate(&module);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].file_index, 0);
+    assert!(diagnostics[0].message.starts_with(&format!(
+        "compressed size {} exceeds uncompressed size {}",
+        file.total_compressed_size, file.total_uncompressed_size
+    )));
+}
+
+#[test]
+fn size_limit_set_before_read_is_kept() {
+    let payload = b"bitmap data".repeat(8);
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::default();
+    module.max_uncompressed_size = Some(16);
+    module.read(&mut reader).unwrap();
+    assert_eq!(module.max_uncompressed_size, Some(16));
+
+    let error = module.extract_tag(0, &mut reader).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::SizeLimitExceeded { limit: 16, .. })
+    ));
+}
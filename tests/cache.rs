@@ -0,0 +1,73 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use h5_dumper::cache::IndexCache;
+use h5_dumper::H5Module;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// A scratch directory below the system temp directory, removed when dropped.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("h5_dumper-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        ScratchDir(path)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn cached_tables_are_reused_until_the_module_changes() {
+    let scratch = ScratchDir::new("cache");
+    let module_path = scratch.0.join("test.module");
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", b"payload");
+    std::fs::write(&module_path, &bytes).unwrap();
+    let mut module = H5Module::from_reader(&mut Cursor::new(&bytes)).unwrap();
+    module.files[0].name = "cached name".to_string();
+
+    let cache = IndexCache::new(scratch.0.join("cache"));
+    assert!(cache.load(&module_path).unwrap().is_none());
+    cache.store(&module_path, &module).unwrap();
+    let cached = cache.load(&module_path).unwrap().unwrap();
+    assert_eq!(cached.files[0].name, "cached name");
+
+    let mut grown = bytes.clone();
+    grown.push(0);
+    std::fs::write(&module_path, &grown).unwrap();
+    assert!(cache.load(&module_path).unwrap().is_none());
+}
+
+#[test]
+fn cached_tables_are_dropped_when_the_module_is_rewritten_in_place() {
+    let scratch = ScratchDir::new("cache-mtime");
+    let module_path = scratch.0.join("test.module");
+    // Campaign modules have no header checksum to tell two versions apart.
+    let bytes = common::single_file_module(23, "objects/rifle.bitmap", "bitm", b"payload");
+    std::fs::write(&module_path, &bytes).unwrap();
+    let modified = SystemTime::now() - Duration::from_secs(60);
+    std::fs::File::options()
+        .write(true)
+        .open(&module_path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+    let module = H5Module::from_reader(&mut Cursor::new(&bytes)).unwrap();
+
+    let cache = IndexCache::new(scratch.0.join("cache"));
+    cache.store(&module_path, &module).unwrap();
+    assert!(cache.load(&module_path).unwrap().is_some());
+
+    let renamed = common::single_file_module(23, "objects/rifle.bitmaq", "bitm", b"payload");
+    assert_eq!(renamed.len(), bytes.len());
+    std::fs::write(&module_path, &renamed).unwrap();
+    assert!(cache.load(&module_path).unwrap().is_none());
+}
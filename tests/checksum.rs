@@ -0,0 +1,80 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::checksum::{self, ProbeReport};
+use h5_dumper::H5Module;
+use std::io::Cursor;
+
+/// Offset of the block table: header, one entry and the name table.
+const BLOCKS: usize = 56 + 88 + "objects/blocks.model".len() + 1;
+/// Offset of the data, after the four 32 byte blocks.
+const DATA: usize = BLOCKS + 4 * 32;
+
+/// A Forge module with one file of four zlib blocks, whose stored checksums are the CRC32 of the uncompressed
+/// blocks.
+fn fixture() -> Vec<u8> {
+    let payload = (0..192).map(|byte| byte as u8).collect::<Vec<u8>>();
+    let file = FixtureFile {
+        name: "objects/blocks.model",
+        group: "mode",
+        payload: &payload,
+        storage: Storage::Blocks(48),
+    };
+    common::build_module(27, &[file])
+}
+
+fn probe_crc32(bytes: &[u8]) -> ProbeReport {
+    let mut reader = Cursor::new(bytes);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+    let candidates = checksum::candidates_by_name(&["crc32".to_string()]).unwrap();
+    checksum::probe(&module, &mut reader, &candidates).unwrap()
+}
+
+fn set_block_field(bytes: &mut [u8], block: usize, offset: usize, value: u32) {
+    let start = BLOCKS + block * 32 + offset;
+    bytes[start..start + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[test]
+fn probe_counts_matching_blocks() {
+    let report = probe_crc32(&fixture());
+    assert_eq!((report.probed, report.skipped), (4, 0));
+    assert_eq!(report.results[0].uncompressed_matches, 4);
+    assert_eq!(report.results[0].compressed_matches, 0);
+}
+
+#[test]
+fn uncompressed_blocks_are_probed_over_their_uncompressed_size() {
+    let mut bytes = fixture();
+    // Block 0 claims to be stored as is, so its 48 uncompressed bytes are whatever is stored at its offset.
+    set_block_field(&mut bytes, 0, 24, 0);
+    let stored = crc32fast::hash(&bytes[DATA..DATA + 48]);
+    set_block_field(&mut bytes, 0, 0, stored);
+    let report = probe_crc32(&bytes);
+    assert_eq!((report.probed, report.skipped), (4, 0));
+    assert_eq!(report.results[0].compressed_matches, 1);
+    assert_eq!(report.results[0].uncompressed_matches, 4);
+}
+
+#[test]
+fn corrupt_blocks_are_skipped() {
+    let mut bytes = fixture();
+    // Block 0: garbage instead of a zlib stream.
+    bytes[DATA..DATA + 4].copy_from_slice(b"junk");
+    // Block 1: stored size far past the end of the module.
+    set_block_field(&mut bytes, 1, 12, u32::MAX);
+    // Block 2: uncompressed size larger than the whole file.
+    set_block_field(&mut bytes, 2, 20, u32::MAX);
+    let report = probe_crc32(&bytes);
+    assert_eq!((report.probed, report.skipped), (1, 3));
+    assert_eq!(report.results[0].uncompressed_matches, 1);
+}
+
+#[test]
+fn overflowing_data_offset_is_skipped() {
+    let mut bytes = fixture();
+    // data_offset of the only entry.
+    bytes[56 + 24..56 + 32].copy_from_slice(&u64::MAX.to_le_bytes());
+    let report = probe_crc32(&bytes);
+    assert_eq!((report.probed, report.skipped), (0, 4));
+}
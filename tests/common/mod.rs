@@ -0,0 +1,186 @@
+//! Builders for small synthetic modules used by the integration tests and benchmarks.
+
+#![allow(dead_code)]
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::Write;
+
+/// Module id written into every fixture header.
+pub const MODULE_ID: u64 = 0xDEAD_BEEF;
+/// Build version written into every fixture header.
+pub const BUILD_VERSION: u64 = 12345;
+
+/// A file to place in a fixture module.
+pub struct FixtureFile<'a> {
+    pub name: &'a str,
+    pub group: &'a str,
+    pub payload: &'a [u8],
+    pub storage: Storage,
+}
+
+/// How a fixture file's payload is stored in the data region.
+pub enum Storage {
+    /// Compressed as a whole, with the `COMPRESSED` flag.
+    Compressed,
+    /// Split into zlib-compressed blocks of this size, with the `HAS_BLOCKS` flag.
+    Blocks(usize),
+    /// Stored verbatim with the `RAW_FILE` flag.
+    Raw,
+    /// Stored verbatim without any flag and without blocks.
+    Uncompressed,
+}
+
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Builds a module of the given `version` (23 or 27) holding `files`.
+pub fn build_module(version: u32, files: &[FixtureFile]) -> Vec<u8> {
+    let forge = version == 27;
+    let mut strings = Vec::new();
+    let mut entries = Vec::new();
+    let mut blocks = Vec::new();
+    let mut block_count = 0u32;
+    let mut data = Vec::new();
+
+    for (index, file) in files.iter().enumerate() {
+        let name_offset = strings.len() as u32;
+        strings.extend_from_slice(file.name.as_bytes());
+        strings.push(0);
+
+        let data_offset = data.len() as u64;
+        let (flags, first_block, file_blocks) = match file.storage {
+            Storage::Blocks(block_size) => {
+                let first_block = block_count as i32;
+                let mut compressed_offset = 0u32;
+                for (chunk_index, chunk) in file.payload.chunks(block_size).enumerate() {
+                    let compressed = compress(chunk);
+                    if forge {
+                        blocks.extend_from_slice(&crc32fast::hash(chunk).to_le_bytes());
+                        blocks.extend_from_slice(&[0; 4]);
+                    }
+                    blocks.extend_from_slice(&compressed_offset.to_le_bytes());
+                    blocks.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+                    blocks.extend_from_slice(&((chunk_index * block_size) as u32).to_le_bytes());
+                    blocks.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+                    blocks.extend_from_slice(&1u32.to_le_bytes()); // compressed
+                    if forge {
+                        blocks.extend_from_slice(&0i32.to_le_bytes()); // padding
+                    }
+                    compressed_offset += compressed.len() as u32;
+                    data.extend_from_slice(&compressed);
+                    block_count += 1;
+                }
+                (2u8, first_block, block_count - first_block as u32)
+            }
+            Storage::Compressed => {
+                data.extend_from_slice(&compress(file.payload));
+                (1u8, -1, 0)
+            }
+            Storage::Raw => {
+                data.extend_from_slice(file.payload);
+                (4u8, -1, 0)
+            }
+            Storage::Uncompressed => {
+                data.extend_from_slice(file.payload);
+                (0u8, -1, 0)
+            }
+        };
+        let compressed_size = (data.len() as u64 - data_offset) as u32;
+        let uncompressed_size = file.payload.len() as u32;
+
+        entries.extend_from_slice(&name_offset.to_le_bytes());
+        entries.extend_from_slice(&(-1i32).to_le_bytes()); // parent_file_index
+        entries.extend_from_slice(&0u32.to_le_bytes()); // resource_count
+        entries.extend_from_slice(&(-1i32).to_le_bytes()); // first_resource_index
+        entries.extend_from_slice(&file_blocks.to_le_bytes());
+        entries.extend_from_slice(&first_block.to_le_bytes());
+        entries.extend_from_slice(&data_offset.to_le_bytes());
+        entries.extend_from_slice(&compressed_size.to_le_bytes());
+        entries.extend_from_slice(&uncompressed_size.to_le_bytes());
+        entries.extend_from_slice(&[0, 0, 0, flags]); // alignments, flags
+        entries.extend_from_slice(&(0x1000 + index as i32).to_le_bytes()); // global_tag_id
+        entries.extend_from_slice(&(0xABC + index as i64).to_le_bytes()); // asset_id
+        entries.extend_from_slice(&(0x555 + index as i64).to_le_bytes()); // asset_checksum
+        entries.extend(file.group.bytes().rev());
+        entries.extend_from_slice(&0u32.to_le_bytes()); // uncompressed_header_size
+        entries.extend_from_slice(&uncompressed_size.to_le_bytes());
+        entries.extend_from_slice(&0u32.to_le_bytes()); // uncompressed_resource_size
+        entries.extend_from_slice(&[0; 8]); // block counts, padding
+    }
+
+    let mut module = Vec::new();
+    module.extend_from_slice(b"mohd");
+    module.extend_from_slice(&version.to_le_bytes());
+    module.extend_from_slice(&MODULE_ID.to_le_bytes());
+    module.extend_from_slice(&(files.len() as u32).to_le_bytes());
+    module.extend_from_slice(&0u32.to_le_bytes()); // manifest_count
+    module.extend_from_slice(&(-1i32).to_le_bytes()); // resource_index
+    module.extend_from_slice(&(strings.len() as u32).to_le_bytes());
+    module.extend_from_slice(&0u32.to_le_bytes()); // resource_count
+    module.extend_from_slice(&block_count.to_le_bytes());
+    module.extend_from_slice(&BUILD_VERSION.to_le_bytes());
+    if forge {
+        module.extend_from_slice(&0u64.to_le_bytes()); // checksum
+    }
+    module.extend_from_slice(&entries);
+    module.extend_from_slice(&strings);
+    module.extend_from_slice(&blocks);
+    module.extend_from_slice(&data);
+    module
+}
+
+/// Builds a module of the given `version` holding a single zlib-compressed file without blocks.
+pub fn single_file_module(version: u32, name: &str, group: &str, payload: &[u8]) -> Vec<u8> {
+    build_module(
+        version,
+        &[FixtureFile {
+            name,
+            group,
+            payload,
+            storage: Storage::Compressed,
+        }],
+    )
+}
+
+/// Converts a little-endian module built by `build_module` into its big-endian equivalent in place, reversing
+/// the magic too when `swap_magic` is set.
+pub fn swap_to_big_endian(module: &mut [u8], swap_magic: bool) {
+    fn swap(bytes: &mut [u8], offset: &mut usize, sizes: &[usize]) {
+        for size in sizes {
+            bytes[*offset..*offset + size].reverse();
+            *offset += size;
+        }
+    }
+    let read_u32 =
+        |offset: usize| u32::from_le_bytes(module[offset..offset + 4].try_into().unwrap());
+    let forge = read_u32(4) == 27;
+    let (item_count, strings_size, block_count) = (read_u32(16), read_u32(28), read_u32(36));
+
+    if swap_magic {
+        module[..4].reverse();
+    }
+    let mut offset = 4;
+    swap(module, &mut offset, &[4, 8, 4, 4, 4, 4, 4, 4, 8]);
+    if forge {
+        swap(module, &mut offset, &[8]);
+    }
+    for _ in 0..item_count {
+        swap(module, &mut offset, &[4, 4, 4, 4, 4, 4, 8, 4, 4]);
+        offset += 4; // alignments, flags
+        swap(module, &mut offset, &[4, 8, 8, 4, 4, 4, 4, 2, 2, 2, 2]);
+    }
+    offset += strings_size as usize;
+    for _ in 0..block_count {
+        if forge {
+            swap(module, &mut offset, &[8]);
+        }
+        swap(module, &mut offset, &[4, 4, 4, 4, 4]);
+        if forge {
+            swap(module, &mut offset, &[4]);
+        }
+    }
+}
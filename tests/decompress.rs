@@ -0,0 +1,63 @@
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use h5_dumper::decompress::{BlockDecompressor, ZlibDecompressor};
+use h5_dumper::ModuleError;
+use std::io::Write;
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Runs both the in-memory and the streaming path into a buffer of `size` bytes.
+fn decompress_both(input: &[u8], size: usize) -> [anyhow::Result<Vec<u8>>; 2] {
+    let mut output = vec![0; size];
+    let in_memory = ZlibDecompressor
+        .decompress(input, &mut output)
+        .map(|_| output);
+    let mut output = vec![0; size];
+    let streamed = ZlibDecompressor
+        .decompress_stream(&mut &input[..], &mut output)
+        .map(|_| output);
+    [in_memory, streamed]
+}
+
+fn size_mismatch(result: anyhow::Result<Vec<u8>>) -> (u64, u64) {
+    match result.unwrap_err().downcast::<ModuleError>() {
+        Ok(ModuleError::DecompressedSizeMismatch { expected, actual }) => (expected, actual),
+        other => panic!("unexpected error {other:?}"),
+    }
+}
+
+#[test]
+fn exact_stream_decompresses() {
+    let payload: Vec<u8> = (0..10_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    for result in decompress_both(&compress(&payload), payload.len()) {
+        assert_eq!(result.unwrap(), payload);
+    }
+}
+
+#[test]
+fn short_stream_reports_shortfall() {
+    let compressed = compress(&[1; 100]);
+    for result in decompress_both(&compressed, 120) {
+        assert_eq!(size_mismatch(result), (120, 100));
+    }
+}
+
+#[test]
+fn long_stream_reports_full_length() {
+    let compressed = compress(&[1; 20_000]);
+    for result in decompress_both(&compressed, 100) {
+        assert_eq!(size_mismatch(result), (100, 20_000));
+    }
+}
+
+#[test]
+fn truncated_stream_fails() {
+    let compressed = compress(&(0..255).collect::<Vec<u8>>());
+    for result in decompress_both(&compressed[..compressed.len() / 2], 255) {
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,18 @@
+use h5_dumper::duplicates::ContentDeduplicator;
+use std::path::Path;
+
+#[test]
+fn content_is_only_known_once_recorded() {
+    let mut dedup = ContentDeduplicator::default();
+    assert_eq!(dedup.original_of(b"bitmap data"), None);
+    // Looking content up does not record it, a file that ends up not being written leaves no trace.
+    assert_eq!(dedup.original_of(b"bitmap data"), None);
+
+    dedup.record(b"bitmap data", Path::new("a/rifle.bitmap"));
+    dedup.record(b"bitmap data", Path::new("b/rifle.bitmap"));
+    assert_eq!(
+        dedup.original_of(b"bitmap data"),
+        Some(Path::new("a/rifle.bitmap"))
+    );
+    assert_eq!(dedup.original_of(b"other data"), None);
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::diff::{changed_entries, Change, MatchKey};
+use h5_dumper::H5Module;
+use std::io::Cursor;
+
+fn module(files: &[(&str, &[u8])]) -> H5Module {
+    let files: Vec<_> = files
+        .iter()
+        .map(|&(name, payload)| FixtureFile {
+            name,
+            group: "bitm",
+            payload,
+            storage: Storage::Compressed,
+        })
+        .collect();
+    H5Module::from_reader(&mut Cursor::new(common::build_module(27, &files))).unwrap()
+}
+
+#[test]
+fn added_and_resized_files_are_changed() {
+    let old = module(&[("a", b"same"), ("b", b"old"), ("c", b"gone")]);
+    let new = module(&[("a", b"same"), ("b", b"newer"), ("d", b"added")]);
+
+    assert_eq!(
+        changed_entries(&old, &new, MatchKey::Name),
+        [(1, Change::Modified), (2, Change::Added)]
+    );
+    // Fixture tag ids follow table order, so "d" matches "c" by id.
+    assert_eq!(
+        changed_entries(&old, &new, MatchKey::TagId),
+        [(1, Change::Modified), (2, Change::Modified)]
+    );
+}
@@ -0,0 +1,114 @@
+#![cfg(all(feature = "serde", feature = "cli"))]
+
+mod common;
+
+use common::{FixtureFile, Storage};
+use std::path::PathBuf;
+use std::process::Command;
+
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("h5_dumper-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        ScratchDir(path)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Writes a module with one regular and one empty entry and runs `command` on it, returning stdout.
+fn run(scratch: &ScratchDir, command: &[&str]) -> String {
+    let files = [
+        FixtureFile {
+            name: "objects/rifle.bitmap",
+            group: "bitm",
+            payload: b"bitmap data",
+            storage: Storage::Compressed,
+        },
+        FixtureFile {
+            name: "objects/empty.bitmap",
+            group: "bitm",
+            payload: b"",
+            storage: Storage::Uncompressed,
+        },
+    ];
+    let module = scratch.0.join("test.module");
+    std::fs::write(&module, common::build_module(27, &files)).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_h5_dumper"))
+        .arg("--module-path")
+        .arg(&module)
+        .args(command)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Extracts the module of `run` with `arguments`, returning stdout.
+fn extract(scratch: &ScratchDir, arguments: &[&str]) -> String {
+    let save_path = scratch.0.join("out");
+    let mut command = vec![
+        "extract",
+        "--merge",
+        "--save-path",
+        save_path.to_str().unwrap(),
+    ];
+    command.extend_from_slice(arguments);
+    run(scratch, &command)
+}
+
+#[test]
+fn empty_entries_are_skipped_by_default() {
+    let scratch = ScratchDir::new("empty-skipped");
+    let stdout = extract(&scratch, &[]);
+    assert!(stdout.contains("Extracted 1 file(s)"), "{stdout}");
+    assert!(stdout.contains("skipped 1 file(s)"), "{stdout}");
+    let out = scratch.0.join("out/objects");
+    assert_eq!(
+        std::fs::read(out.join("rifle.bitmap")).unwrap(),
+        b"bitmap data"
+    );
+    assert!(!out.join("empty.bitmap").exists());
+}
+
+#[test]
+fn empty_entries_are_written_as_zero_byte_files_when_included() {
+    for arguments in [&["--exclude-empty", "false"][..], &["--include-empty"]] {
+        let scratch = ScratchDir::new("empty-written");
+        let stdout = extract(&scratch, arguments);
+        assert!(stdout.contains("Extracted 2 file(s)"), "{stdout}");
+        assert!(stdout.contains("skipped 0 file(s)"), "{stdout}");
+        let empty = scratch.0.join("out/objects/empty.bitmap");
+        assert_eq!(std::fs::metadata(empty).unwrap().len(), 0);
+    }
+}
+
+#[test]
+fn empty_entries_are_listed_unless_excluded() {
+    let scratch = ScratchDir::new("empty-list");
+    let stdout = run(&scratch, &["list"]);
+    assert!(stdout.contains("objects/empty.bitmap"), "{stdout}");
+    assert!(stdout.contains("2 file(s)"), "{stdout}");
+
+    let stdout = run(&scratch, &["list", "--exclude-empty"]);
+    assert!(!stdout.contains("objects/empty.bitmap"), "{stdout}");
+    assert!(stdout.contains("objects/rifle.bitmap"), "{stdout}");
+    assert!(
+        stdout.contains("1 of 2 file(s), 1 empty file(s) excluded"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn quiet_extraction_prints_no_summary() {
+    let scratch = ScratchDir::new("empty-quiet");
+    let stdout = extract(&scratch, &["--quiet", "--global-dedup"]);
+    assert_eq!(stdout, "");
+    assert!(scratch.0.join("out/objects/rifle.bitmap").exists());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::{Endianness, H5Module};
+use std::io::Cursor;
+
+fn fixture(version: u32) -> Vec<u8> {
+    common::build_module(
+        version,
+        &[
+            FixtureFile {
+                name: "objects/rifle.bitmap",
+                group: "bitm",
+                payload: &b"bitmap data".repeat(8),
+                storage: Storage::Compressed,
+            },
+            FixtureFile {
+                name: "objects/blocks.model",
+                group: "mode",
+                payload: &[7; 64],
+                storage: Storage::Blocks(16),
+            },
+        ],
+    )
+}
+
+fn assert_matches_little_endian(big: Vec<u8>, version: u32) {
+    let mut little_reader = Cursor::new(fixture(version));
+    let little = H5Module::from_reader(&mut little_reader).unwrap();
+    let mut reader = Cursor::new(big);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+
+    assert_eq!(module.header.endianness, Endianness::Big);
+    assert_eq!(module.header.version, version);
+    assert_eq!(module.header.module_id, common::MODULE_ID);
+    assert_eq!(module.header.build_version, common::BUILD_VERSION);
+    assert_eq!(module.blocks.len(), 4);
+    for (file, expected) in module.files.iter().zip(&little.files) {
+        assert_eq!(file.name, expected.name);
+        assert_eq!(file.group_tag, expected.group_tag);
+        assert_eq!(file.asset_id, expected.asset_id);
+        assert_eq!(file.global_tag_id, expected.global_tag_id);
+    }
+    for index in 0..module.files.len() {
+        assert_eq!(
+            module.extract_tag(index as u32, &mut reader).unwrap(),
+            little
+                .extract_tag(index as u32, &mut little_reader)
+                .unwrap()
+        );
+    }
+}
+
+#[test]
+fn pc_modules_are_little_endian() {
+    let module = H5Module::from_reader(&mut Cursor::new(fixture(27))).unwrap();
+    assert_eq!(module.header.endianness, Endianness::Little);
+}
+
+#[test]
+fn swapped_magic_is_read_big_endian() {
+    for version in [23, 27] {
+        let mut bytes = fixture(version);
+        common::swap_to_big_endian(&mut bytes, true);
+        assert_matches_little_endian(bytes, version);
+    }
+}
+
+#[test]
+fn swapped_version_is_read_big_endian() {
+    let mut bytes = fixture(27);
+    common::swap_to_big_endian(&mut bytes, false);
+    assert_matches_little_endian(bytes, 27);
+}
+
+#[test]
+fn forced_endianness_overrides_detection() {
+    let mut bytes = fixture(27);
+    common::swap_to_big_endian(&mut bytes, false);
+    let mut module = H5Module::default();
+    module.endianness = Some(Endianness::Little);
+    assert!(module.read(&mut Cursor::new(&bytes)).is_err());
+
+    module.endianness = Some(Endianness::Big);
+    module.read(&mut Cursor::new(&bytes)).unwrap();
+    assert_eq!(module.files.len(), 2);
+}
@@ -0,0 +1,18 @@
+mod common;
+
+use h5_dumper::{export, H5Module};
+use std::io::Cursor;
+
+#[test]
+fn file_rows_quote_names_with_commas() {
+    let bytes = common::single_file_module(27, "levels/a,b", "mat ", b"material");
+    let module = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(export::FILE_CSV_HEADER).unwrap();
+    export::write_file_rows(&mut writer, "test.module", &module).unwrap();
+    let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    let row = csv.lines().nth(1).unwrap();
+    assert!(row.starts_with("test.module,\"levels/a,b\",mat ,"));
+    assert!(row.ends_with(",true,false"));
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::{FileFlags, H5Module, ModuleVersion};
+use std::io::Cursor;
+
+/// Parses `bytes` with `H5Module::read` and checks the header and file table common to every fixture.
+fn read_fixture(bytes: Vec<u8>, version: u32, files: usize) -> (H5Module, Cursor<Vec<u8>>) {
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::default();
+    module.read(&mut reader).unwrap();
+
+    assert_eq!(module.header.magic, "mohd");
+    assert_eq!(module.header.version, version);
+    assert_eq!(module.header.module_id, common::MODULE_ID);
+    assert_eq!(module.header.item_count as usize, files);
+    assert_eq!(module.header.build_version, common::BUILD_VERSION);
+    assert_eq!(module.header.build_string(), "12345 (0x3039)");
+    assert!(module
+        .header
+        .to_string()
+        .contains("build_version:  12345 (0x3039)"));
+    assert_eq!(module.files.len(), files);
+    let header_size = ModuleVersion::try_from(version).unwrap().header_size();
+    assert_eq!(module.header.size().unwrap(), header_size);
+    (module, reader)
+}
+
+#[test]
+fn minimal_forge_module() {
+    let payload = b"hello bitmap data".repeat(10);
+    let bytes = common::single_file_module(27, "objects/weapons/rifle.bitmap", "bitm", &payload);
+    let (mut module, mut reader) = read_fixture(bytes, 27, 1);
+
+    let file = &module.files[0];
+    assert_eq!(file.name, "objects/weapons/rifle.bitmap");
+    assert_eq!(file.group_tag, "bitm");
+    assert_eq!(file.flags, FileFlags::COMPRESSED);
+    assert_eq!(file.total_uncompressed_size as usize, payload.len());
+
+    module.read_tag(0, &mut reader).unwrap();
+    assert_eq!(module.files[0].data, payload);
+}
+
+#[test]
+fn minimal_campaign_module() {
+    let model: Vec<u8> = (0..=255).collect();
+    let bytes = common::build_module(
+        23,
+        &[
+            FixtureFile {
+                name: "levels/tiny",
+                group: "mat ",
+                payload: b"ab",
+                storage: Storage::Compressed,
+            },
+            FixtureFile {
+                name: "objects/rifle.model",
+                group: "mode",
+                payload: &model,
+                storage: Storage::Blocks(100),
+            },
+        ],
+    );
+    let (mut module, mut reader) = read_fixture(bytes, 23, 2);
+    assert_eq!(module.header.checksum, 0);
+    assert_eq!(module.blocks.len(), 3);
+    assert!(module.blocks.iter().all(|block| block.checksum == 0));
+    assert_eq!(module.files[0].group_tag, "mat ");
+    assert_eq!(module.files[1].name, "objects/rifle.model");
+
+    module.read_tag(0, &mut reader).unwrap();
+    module.read_tag(1, &mut reader).unwrap();
+    assert_eq!(module.files[0].data, b"ab");
+    assert_eq!(module.files[1].data, model);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use h5_dumper::common::CountingReader;
+use h5_dumper::H5Module;
+use std::io::{BufReader, Cursor, Read};
+
+#[test]
+fn parses_module_from_cursor() {
+    let payload = b"bitmap data".repeat(8);
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let mut reader = Cursor::new(bytes);
+
+    let module = H5Module::from_reader(&mut reader).unwrap();
+    assert_eq!(module.header.module_id, common::MODULE_ID);
+    assert_eq!(module.files.len(), 1);
+    assert_eq!(module.files[0].name, "objects/rifle.bitmap");
+    assert_eq!(module.extract_tag(0, &mut reader).unwrap(), payload);
+}
+
+#[test]
+fn entries_walk_metadata_without_extracting() {
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", b"bitmap data");
+    let module = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+    let entries: Vec<_> = module.entries().collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "objects/rifle.bitmap");
+    assert!(entries[0].data.is_empty());
+}
+
+#[test]
+fn read_parses_tables_from_a_stream() {
+    let payload = b"bitmap data".repeat(8);
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let seekable = H5Module::from_reader(&mut Cursor::new(&bytes)).unwrap();
+    assert_eq!(seekable.file_len, Some(bytes.len() as u64));
+
+    // A byte slice reads but cannot seek.
+    let mut reader = BufReader::with_capacity(7, CountingReader::new(&bytes[..]));
+    let mut module = H5Module::default();
+    module.read(&mut reader).unwrap();
+    assert_eq!(module.file_len, None);
+    assert_eq!(module.data_offset, seekable.data_offset);
+    assert_eq!(module.files[0].name, "objects/rifle.bitmap");
+
+    // The reader is left at the start of the data section.
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).unwrap();
+    assert_eq!(data, &bytes[module.data_offset as usize..]);
+    assert_eq!(reader.into_inner().position(), bytes.len() as u64);
+}
+
+#[test]
+fn tables_are_reserved_from_the_header_counts() {
+    let names: Vec<_> = (0..33).map(|i| format!("objects/tag_{i}.model")).collect();
+    let files: Vec<_> = names
+        .iter()
+        .map(|name| common::FixtureFile {
+            name,
+            group: "mode",
+            payload: b"two blocks",
+            storage: common::Storage::Blocks(5),
+        })
+        .collect();
+    let module = H5Module::from_reader(&mut Cursor::new(common::build_module(27, &files))).unwrap();
+    assert_eq!(module.files.capacity(), 33);
+    assert_eq!(module.blocks.capacity(), 66);
+}
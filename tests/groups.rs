@@ -0,0 +1,42 @@
+use h5_dumper::groups::{fourcc_for_name, name_for_fourcc, GroupTag};
+
+#[test]
+fn names_and_fourccs_round_trip() {
+    assert_eq!(fourcc_for_name("material"), Some("mat "));
+    assert_eq!(fourcc_for_name("Bitmap"), Some("bitm"));
+    assert_eq!(name_for_fourcc("mat"), Some("material"));
+    assert_eq!(name_for_fourcc("snd!"), Some("sound"));
+    assert_eq!(fourcc_for_name("bitm"), None);
+    assert_eq!(name_for_fourcc("????"), None);
+}
+
+#[test]
+fn group_tags_parse_into_known_groups() {
+    assert_eq!(GroupTag::from_fourcc("bitm"), GroupTag::Bitmap);
+    assert_eq!(GroupTag::from("mat"), GroupTag::Material);
+    assert_eq!(GroupTag::from_le_bytes(*b"!dns"), GroupTag::Sound);
+    assert_eq!(GroupTag::Material.as_fourcc(), Some("mat "));
+    assert_eq!(GroupTag::RenderModel.name(), Some("render_model"));
+
+    let custom = GroupTag::from_fourcc("zzzz");
+    assert!(matches!(custom, GroupTag::Custom(group) if group.to_bytes() == *b"zzzz"));
+    assert_eq!(custom.name(), None);
+    assert_eq!(custom.to_string(), "zzzz");
+    assert_eq!(
+        GroupTag::from_le_bytes(*b"\xffabc").to_string(),
+        "cba\u{fffd}"
+    );
+}
+
+#[test]
+fn known_groups_are_never_custom() {
+    assert_eq!(GroupTag::from_bytes(*b"bitm"), GroupTag::Bitmap);
+    assert_eq!(GroupTag::from_bytes(*b"mat "), GroupTag::Material);
+    assert_eq!(
+        GroupTag::from_bytes(*b"zzzz"),
+        GroupTag::from_fourcc("zzzz")
+    );
+    for group in [GroupTag::Sound, GroupTag::from_fourcc("zzzz")] {
+        assert_eq!(GroupTag::from_bytes(group.to_bytes()), group);
+    }
+}
@@ -0,0 +1,34 @@
+use h5_dumper::hash::{to_hex, Crc32Writer, HashAlgorithm};
+use std::io::Write;
+
+#[test]
+fn crc32_digest_is_big_endian() {
+    let digest = HashAlgorithm::Crc32.digest(b"123456789");
+    assert_eq!(to_hex(&digest), "cbf43926");
+    assert!(HashAlgorithm::Crc32.matches_checksum(&digest, 0xcbf43926));
+    assert!(!HashAlgorithm::Crc32.matches_checksum(&digest, 0x2639f4cb));
+}
+
+#[test]
+fn blake3_compares_leading_bytes_against_checksum() {
+    let digest = HashAlgorithm::Blake3.digest(b"");
+    assert_eq!(
+        to_hex(&digest),
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+    );
+    let checksum = u64::from_le_bytes(digest[..8].try_into().unwrap()) as i64;
+    assert!(HashAlgorithm::Blake3.matches_checksum(&digest, checksum));
+}
+
+#[test]
+fn crc32_writer_hashes_what_it_writes() {
+    let mut hasher = crc32fast::Hasher::new();
+    let mut first = Crc32Writer::new(Vec::new(), &mut hasher);
+    first.write_all(b"12345").unwrap();
+    assert_eq!(first.into_inner(), b"12345");
+    // A second writer continues the same checksum, as for the sections of one tag.
+    let mut second = Crc32Writer::new(Vec::new(), &mut hasher);
+    second.write_all(b"6789").unwrap();
+    assert_eq!(second.into_inner(), b"6789");
+    assert_eq!(hasher.finalize(), 0xcbf43926);
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::pool::BufferPool;
+use h5_dumper::H5Module;
+use std::io::Cursor;
+
+/// Extracts every tag of a module with many tags of every storage kind through one reused buffer pool, as the
+/// throughput benchmark does, and checks that no buffer leaks data into the next tag.
+#[test]
+fn every_tag_of_a_large_module_round_trips() {
+    let payloads: Vec<Vec<u8>> = (0..96usize)
+        .map(|i| (0..(1000 + i * 997)).map(|j| (j * 31 + i) as u8).collect())
+        .collect();
+    let names: Vec<_> = (0..payloads.len())
+        .map(|i| format!("objects/tag_{i}.model"))
+        .collect();
+    let files: Vec<_> = payloads
+        .iter()
+        .zip(&names)
+        .enumerate()
+        .map(|(i, (payload, name))| FixtureFile {
+            name,
+            group: "mode",
+            payload,
+            storage: match i % 3 {
+                0 => Storage::Compressed,
+                1 => Storage::Blocks(0x1000),
+                _ => Storage::Raw,
+            },
+        })
+        .collect();
+    let mut reader = Cursor::new(common::build_module(27, &files));
+    let module = H5Module::from_reader(&mut reader).unwrap();
+    let mut pool = BufferPool::default();
+
+    for (index, payload) in payloads.iter().enumerate() {
+        let data = module
+            .extract_tag_with_pool(index as u32, &mut reader, &mut pool)
+            .unwrap();
+        assert_eq!(&data, payload, "tag {index}");
+        pool.give(data);
+    }
+}
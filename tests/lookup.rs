@@ -0,0 +1,107 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::H5Module;
+use std::io::Cursor;
+
+fn two_file_module() -> H5Module {
+    let files = ["objects/first.bitmap", "objects/second.bitmap"].map(|name| FixtureFile {
+        name,
+        group: "bitm",
+        payload: b"data",
+        storage: Storage::Compressed,
+    });
+    let bytes = common::build_module(27, &files);
+    H5Module::from_reader(&mut Cursor::new(bytes)).unwrap()
+}
+
+#[test]
+fn files_are_found_by_global_tag_id() {
+    let module = two_file_module();
+    let file = module.find_by_global_id(0x1001).unwrap();
+    assert_eq!(file.name, "objects/second.bitmap");
+    assert_eq!(module.find_by_tag_id(0x1001), Some(1));
+    assert!(module.find_by_global_id(0x2000).is_none());
+}
+
+#[test]
+fn files_are_found_by_asset_id() {
+    let module = two_file_module();
+    assert_eq!(
+        module.find_by_asset_id(0xABC).unwrap().name,
+        "objects/first.bitmap"
+    );
+    assert_eq!(module.find_by_asset_id_index(0xABD), Some(1));
+}
+
+#[test]
+fn files_without_asset_id_are_not_found() {
+    let mut module = two_file_module();
+    module.files[0].asset_id = -1;
+    assert!(module.find_by_asset_id(-1).is_none());
+}
+
+#[test]
+fn lookups_match_a_linear_scan_with_duplicates() {
+    let names = [
+        "objects/first.bitmap",
+        "objects/second.bitmap",
+        "objects/first.bitmap",
+        "objects/third.bitmap",
+        "objects/second.bitmap",
+    ];
+    let files = names.map(|name| FixtureFile {
+        name,
+        group: "bitm",
+        payload: b"data",
+        storage: Storage::Compressed,
+    });
+    let mut module =
+        H5Module::from_reader(&mut Cursor::new(common::build_module(27, &files))).unwrap();
+    // Duplicate ids before the first lookup, the tables are only built once.
+    module.files[3].global_tag_id = module.files[1].global_tag_id;
+    module.files[4].global_tag_id = module.files[1].global_tag_id;
+    module.files[2].asset_id = module.files[0].asset_id;
+    module.files[3].asset_id = -1;
+
+    for file in &module.files {
+        let by_name = module
+            .files
+            .iter()
+            .position(|other| other.name == file.name);
+        assert_eq!(module.find_by_name(&file.name), by_name);
+        let by_tag_id = module
+            .files
+            .iter()
+            .position(|other| other.global_tag_id == file.global_tag_id);
+        assert_eq!(module.find_by_tag_id(file.global_tag_id), by_tag_id);
+        let by_asset_id = module
+            .files
+            .iter()
+            .position(|other| other.asset_id == file.asset_id && other.asset_id != -1);
+        assert_eq!(module.find_by_asset_id_index(file.asset_id), by_asset_id);
+    }
+    assert_eq!(module.find_by_name("objects/first.bitmap"), Some(0));
+    assert_eq!(module.find_by_name("objects/second.bitmap"), Some(1));
+    assert_eq!(
+        module.find_by_tag_id(module.files[4].global_tag_id),
+        Some(1)
+    );
+    assert_eq!(
+        module.find_by_asset_id_index(module.files[2].asset_id),
+        Some(0)
+    );
+    assert_eq!(module.find_by_name("objects/missing.bitmap"), None);
+}
+
+#[test]
+fn lookups_are_rebuilt_after_invalidation() {
+    let mut module = two_file_module();
+    assert_eq!(module.find_by_name("objects/first.bitmap"), Some(0));
+
+    module.files[0].name = "objects/renamed.bitmap".to_string();
+    assert_eq!(module.find_by_name("objects/renamed.bitmap"), None);
+    module.invalidate_lookups();
+    assert_eq!(module.find_by_name("objects/renamed.bitmap"), Some(0));
+    assert_eq!(module.find_by_name("objects/first.bitmap"), None);
+}
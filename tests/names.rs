@@ -0,0 +1,90 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::{H5Module, ModuleError, ModuleFileEntry};
+use std::io::Cursor;
+
+#[test]
+fn names_are_sliced_out_of_the_string_table() {
+    let strings = b"first\0second\0unterminated";
+    let mut file = ModuleFileEntry::default();
+    for (offset, name) in [(0, "first"), (6, "second"), (13, "unterminated")] {
+        file.name_offset = offset;
+        file.read_name(strings).unwrap();
+        assert_eq!(file.name, name);
+    }
+
+    file.name_offset = strings.len() as u32;
+    let error = file.read_name(strings).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::NameOffsetOutOfBounds { .. })
+    ));
+}
+
+#[test]
+fn invalid_utf8_in_names_is_replaced() {
+    let mut file = ModuleFileEntry::default();
+    file.read_name(b"bad\xffname\0").unwrap();
+    assert_eq!(file.name, "bad\u{fffd}name");
+}
+
+#[test]
+fn lossy_strings_are_counted() {
+    let mut bytes = common::build_module(
+        27,
+        &[
+            FixtureFile {
+                name: "objects/fine.bitmap",
+                group: "bitm",
+                payload: b"data",
+                storage: Storage::Compressed,
+            },
+            FixtureFile {
+                name: "objects/bad.bitmap",
+                group: "bitm",
+                payload: b"data",
+                storage: Storage::Compressed,
+            },
+        ],
+    );
+    let position = bytes
+        .windows(3)
+        .position(|window| window == b"bad")
+        .unwrap();
+    bytes[position] = 0xff;
+    let module = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+    assert_eq!(module.files[1].name, "objects/\u{fffd}ad.bitmap");
+    assert_eq!(module.lossy_strings, 1);
+}
+
+#[test]
+fn zeroed_name_table_gets_synthetic_names_when_lenient() {
+    let files = [
+        ("objects/rifle.bitmap", "bitm"),
+        ("objects/rifle.model", "mode"),
+    ]
+    .map(|(name, group)| FixtureFile {
+        name,
+        group,
+        payload: b"data",
+        storage: Storage::Compressed,
+    });
+    let mut bytes = common::build_module(27, &files);
+    let mut module = H5Module::default();
+    module.lenient_names = true;
+    module.read(&mut Cursor::new(&bytes)).unwrap();
+    assert!(!module.synthetic_names);
+    assert_eq!(module.files[1].name, "objects/rifle.model");
+
+    // The string table follows the 56 byte header and the 88 byte entries, there are no manifest indices.
+    let strings = 56 + 88 * files.len();
+    bytes[strings..strings + module.header.strings_size as usize].fill(0);
+    let mut module = H5Module::default();
+    module.lenient_names = true;
+    module.read(&mut Cursor::new(&bytes)).unwrap();
+    assert!(module.synthetic_names);
+    let names: Vec<_> = module.files.iter().map(|file| file.name.as_str()).collect();
+    assert_eq!(names, ["0_bitm", "1_mode"]);
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "tokio")]
+
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::H5Module;
+use std::io::Cursor;
+
+#[tokio::test]
+async fn async_extraction_matches_sync_extraction() {
+    let payload: Vec<u8> = (0..200).collect();
+    let storages = [
+        Storage::Compressed,
+        Storage::Blocks(64),
+        Storage::Raw,
+        Storage::Uncompressed,
+    ];
+    let names = ["a", "b", "c", "d"];
+    let files: Vec<_> = storages
+        .into_iter()
+        .zip(names)
+        .map(|(storage, name)| FixtureFile {
+            name,
+            group: "bitm",
+            payload: &payload,
+            storage,
+        })
+        .collect();
+    let bytes = common::build_module(27, &files);
+    let module = H5Module::from_reader(&mut Cursor::new(&bytes)).unwrap();
+
+    let mut reader = Cursor::new(bytes);
+    for index in 0..files.len() as u32 {
+        let data = module.extract_tag_async(index, &mut reader).await.unwrap();
+        assert_eq!(data, payload, "tag {index}");
+    }
+}
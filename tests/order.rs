@@ -0,0 +1,28 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::{EntryOrder, H5Module};
+use std::io::Cursor;
+
+#[test]
+fn sorted_indices_order_by_key() {
+    let file = |name, group| FixtureFile {
+        name,
+        group,
+        payload: b"payload",
+        storage: Storage::Compressed,
+    };
+    let bytes = common::build_module(
+        27,
+        &[
+            file("sound/b.sound", "snd!"),
+            file("bitmaps/c.bitmap", "bitm"),
+            file("sound/a.sound", "snd!"),
+        ],
+    );
+    let module = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+    assert_eq!(module.sorted_indices(EntryOrder::Name), [1, 2, 0]);
+    assert_eq!(module.sorted_indices(EntryOrder::Group), [1, 2, 0]);
+    assert_eq!(module.sorted_indices(EntryOrder::Offset), [0, 1, 2]);
+}
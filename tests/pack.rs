@@ -0,0 +1,185 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::checksum::Crc32;
+use h5_dumper::export::ModuleManifest;
+use h5_dumper::manifest::SCHEMA_VERSION;
+use h5_dumper::pack::{self, PackManifest, PackOptions};
+use h5_dumper::paths::sanitize_path;
+use h5_dumper::H5Module;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// A scratch directory below the system temp directory, removed when dropped.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("h5_dumper-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        ScratchDir(path)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn fixture() -> Vec<u8> {
+    let text = b"bitmap data".repeat(8);
+    let blocks = (0..=255).collect::<Vec<u8>>();
+    let files = [
+        ("objects/rifle.bitmap", Storage::Compressed, &text[..]),
+        ("objects/blocks.model", Storage::Blocks(48), &blocks[..]),
+        ("sound/raw.sound", Storage::Raw, &text[..]),
+        ("levels/plain.scenario", Storage::Uncompressed, &blocks[..]),
+    ]
+    .map(|(name, storage, payload)| FixtureFile {
+        name,
+        group: "bitm",
+        payload,
+        storage,
+    });
+    common::build_module(27, &files)
+}
+
+/// Extracts every tag of `module` below `directory` and returns the module's manifest.
+fn extract(module: &[u8], directory: &Path) -> PackManifest {
+    let mut reader = Cursor::new(module);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+    for index in 0..module.files.len() {
+        let path = directory.join(sanitize_path(&module.files[index].name));
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let data = module.extract_tag(index as u32, &mut reader).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+    let json = serde_json::to_string(&ModuleManifest::new(Path::new("test.module"), &module));
+    PackManifest::from_json(&json.unwrap(), None).unwrap()
+}
+
+#[test]
+fn packed_module_round_trips() {
+    let scratch = ScratchDir::new("pack");
+    let original = fixture();
+    let manifest = extract(&original, &scratch.0);
+
+    let options = PackOptions {
+        block_size: 100,
+        block_checksum: Some(&Crc32),
+    };
+    let mut packed = Vec::new();
+    pack::pack(&manifest, &scratch.0, &options, &mut packed).unwrap();
+
+    let mut original_reader = Cursor::new(original);
+    let original = H5Module::from_reader(&mut original_reader).unwrap();
+    let mut reader = Cursor::new(packed);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+    assert_eq!(module.header.module_id, common::MODULE_ID);
+    assert_eq!(module.header.build_version, common::BUILD_VERSION);
+    assert_eq!(module.blocks.len(), 3);
+    assert_eq!(module.files[1].tag_block_count, 3);
+    for (index, (file, expected)) in module.files.iter().zip(&original.files).enumerate() {
+        assert_eq!(file.name, expected.name);
+        assert_eq!(file.flags, expected.flags);
+        assert_eq!(file.asset_id, expected.asset_id);
+        assert_eq!(
+            module.extract_tag(index as u32, &mut reader).unwrap(),
+            original
+                .extract_tag(index as u32, &mut original_reader)
+                .unwrap()
+        );
+    }
+    let checksums = module.extract_tag_verified(1, &mut reader, &mut Default::default(), &Crc32);
+    assert!(checksums.is_ok());
+}
+
+/// Offset of the group tag of entry `index`, after the 56 byte header.
+fn group_tag_offset(index: usize) -> usize {
+    56 + index * 88 + 64
+}
+
+#[test]
+fn stored_group_tags_are_packed_verbatim() {
+    let scratch = ScratchDir::new("pack-group-tags");
+    let mut original = fixture();
+    // NUL padded, not valid UTF-8, and a regular tag that is changed in the manifest below.
+    let stored: [&[u8; 4]; 3] = [b"\0tib", b"\xffdom", b"mtib"];
+    for (index, group_tag) in stored.iter().enumerate() {
+        let offset = group_tag_offset(index);
+        original[offset..offset + 4].copy_from_slice(*group_tag);
+    }
+    let mut manifest = extract(&original, &scratch.0);
+    manifest.files[2].group_tag = "snd!".to_string();
+
+    let mut packed = Vec::new();
+    pack::pack(&manifest, &scratch.0, &PackOptions::default(), &mut packed).unwrap();
+    let group_tag = |index| &packed[group_tag_offset(index)..group_tag_offset(index) + 4];
+    assert_eq!(group_tag(0), b"\0tib");
+    assert_eq!(group_tag(1), b"\xffdom");
+    assert_eq!(group_tag(2), b"!dns");
+}
+
+#[test]
+fn changed_tags_are_repacked_with_their_new_size() {
+    let scratch = ScratchDir::new("pack-changed");
+    let manifest = extract(&fixture(), &scratch.0);
+    let changed = b"a longer bitmap than before".repeat(20);
+    std::fs::write(scratch.0.join("objects/rifle.bitmap"), &changed).unwrap();
+
+    let mut packed = Vec::new();
+    pack::pack(&manifest, &scratch.0, &PackOptions::default(), &mut packed).unwrap();
+    let mut reader = Cursor::new(packed);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+    assert_eq!(module.files[0].uncompressed_tag_size, changed.len() as u32);
+    assert_eq!(module.extract_tag(0, &mut reader).unwrap(), changed);
+}
+
+#[test]
+fn missing_tag_is_an_error() {
+    let scratch = ScratchDir::new("pack-missing");
+    let manifest = extract(&fixture(), &scratch.0);
+    std::fs::remove_file(scratch.0.join("sound/raw.sound")).unwrap();
+
+    let error = pack::pack(
+        &manifest,
+        &scratch.0,
+        &PackOptions::default(),
+        &mut Vec::new(),
+    );
+    assert!(error.unwrap_err().to_string().contains("sound/raw.sound"));
+}
+
+#[test]
+fn manifest_of_several_modules_needs_a_name() {
+    let module = H5Module::from_reader(&mut Cursor::new(fixture())).unwrap();
+    let json = serde_json::json!({
+        "schema_version": SCHEMA_VERSION,
+        "modules": [
+            ModuleManifest::new(Path::new("deploy/a.module"), &module),
+            ModuleManifest::new(Path::new("deploy/b.module"), &module),
+        ]
+    })
+    .to_string();
+
+    assert!(PackManifest::from_json(&json, None).is_err());
+    assert!(PackManifest::from_json(&json, Some("c.module")).is_err());
+    let manifest = PackManifest::from_json(&json, Some("b.module")).unwrap();
+    assert_eq!(manifest.files.len(), 4);
+}
+
+#[test]
+fn manifest_from_a_newer_schema_is_rejected() {
+    let module = H5Module::from_reader(&mut Cursor::new(fixture())).unwrap();
+    let json = serde_json::json!({
+        "schema_version": SCHEMA_VERSION + 1,
+        "modules": [ModuleManifest::new(Path::new("deploy/a.module"), &module)]
+    })
+    .to_string();
+
+    let error = PackManifest::from_json(&json, None).unwrap_err();
+    assert!(error.to_string().contains("schema version"));
+}
@@ -0,0 +1,96 @@
+use h5_dumper::paths::{flat_name, sanitize_path, with_group_extension, UniquePaths};
+use std::path::{Component, Path, PathBuf};
+
+#[test]
+fn parent_components_stay_inside_save_path() {
+    let save_path = Path::new("save");
+    let path = save_path.join(sanitize_path("../../etc/passwd"));
+    assert_eq!(path, Path::new("save/etc/passwd"));
+    assert!(path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_))));
+}
+
+#[test]
+fn absolute_names_stay_inside_save_path() {
+    let save_path = Path::new("save");
+    assert_eq!(
+        save_path.join(sanitize_path("/etc/passwd")),
+        Path::new("save/etc/passwd")
+    );
+    assert_eq!(
+        save_path.join(sanitize_path("C:\\Windows\\..\\..\\win.ini")),
+        Path::new("save/C_/Windows/win.ini")
+    );
+}
+
+#[test]
+fn names_without_components_map_to_placeholder() {
+    assert_eq!(sanitize_path("../.."), Path::new("_"));
+}
+
+#[test]
+fn reserved_characters_are_replaced() {
+    assert_eq!(
+        sanitize_path("foo:bar?<baz>|qux"),
+        Path::new("foo_bar__baz__qux")
+    );
+    assert_eq!(
+        sanitize_path("objects\\a*b\"c.model"),
+        Path::new("objects/a_b_c.model")
+    );
+}
+
+#[test]
+fn reserved_device_names_are_renamed() {
+    assert_eq!(sanitize_path("sound/CON"), Path::new("sound/CON_"));
+    assert_eq!(sanitize_path("sound/nul.wav"), Path::new("sound/nul_.wav"));
+    assert_eq!(sanitize_path("sound/console"), Path::new("sound/console"));
+}
+
+#[test]
+fn trailing_dots_and_spaces_are_removed() {
+    assert_eq!(sanitize_path("levels/tiny. "), Path::new("levels/tiny"));
+}
+
+#[test]
+fn group_extension_is_appended_once() {
+    assert_eq!(
+        with_group_extension(sanitize_path("objects/rifle"), "BITM"),
+        Path::new("objects/rifle.bitm")
+    );
+    assert_eq!(
+        with_group_extension(sanitize_path("levels/tiny"), "mat "),
+        Path::new("levels/tiny.mat")
+    );
+    assert_eq!(
+        with_group_extension(sanitize_path("objects/rifle.bitm"), "bitm"),
+        Path::new("objects/rifle.bitm")
+    );
+    assert_eq!(
+        with_group_extension(sanitize_path("objects/rifle"), "    "),
+        Path::new("objects/rifle")
+    );
+}
+
+#[test]
+fn flat_names_fall_back_to_the_global_tag_id() {
+    assert_eq!(
+        flat_name(0xdead_beef, 7, "bitm"),
+        PathBuf::from("00000000deadbeef.bitm")
+    );
+    assert_eq!(flat_name(-1, -2, "mat "), PathBuf::from("fffffffe.mat"));
+}
+
+#[test]
+fn unique_paths_suffix_collisions() {
+    let mut paths = UniquePaths::default();
+    assert_eq!(paths.claim("a.bitm".into()), PathBuf::from("a.bitm"));
+    assert_eq!(paths.claim("a.bitm".into()), PathBuf::from("a_1.bitm"));
+    assert_eq!(paths.claim("a.bitm".into()), PathBuf::from("a_2.bitm"));
+    assert_eq!(paths.claim("b".into()), PathBuf::from("b"));
+    assert_eq!(paths.claim("b".into()), PathBuf::from("b_1"));
+    assert!(!paths.insert("a_1.bitm".into()));
+    assert!(paths.insert("c".into()));
+    assert_eq!(paths.claim("c".into()), PathBuf::from("c_1"));
+}
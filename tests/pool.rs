@@ -0,0 +1,34 @@
+use h5_dumper::pool::BufferPool;
+
+#[test]
+fn returned_buffers_are_reused() {
+    let mut pool = BufferPool::new(2);
+    let buffer = pool.take(64);
+    let pointer = buffer.as_ptr();
+    pool.give(buffer);
+    assert_eq!(pool.len(), 1);
+
+    let buffer = pool.take(32);
+    assert_eq!(buffer.as_ptr(), pointer);
+    assert_eq!(buffer, [0; 32]);
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn pool_keeps_at_most_max_buffers() {
+    let mut pool = BufferPool::new(2);
+    for _ in 0..3 {
+        pool.give(vec![1; 16]);
+    }
+    assert_eq!(pool.len(), 2);
+}
+
+#[test]
+fn buffers_over_the_capacity_limit_are_dropped() {
+    let mut pool = BufferPool::with_max_capacity(4, 1024);
+    pool.give(Vec::with_capacity(1024));
+    pool.give(Vec::with_capacity(1025));
+    pool.give(Vec::new());
+    assert_eq!(pool.len(), 1);
+    assert!(pool.take(2048).capacity() >= 2048);
+}
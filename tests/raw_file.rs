@@ -0,0 +1,79 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::{FileFlags, H5Module, ModuleError};
+use std::io::Cursor;
+
+#[test]
+fn raw_entries_are_read_verbatim() {
+    let payload = b"not a zlib stream";
+    let bytes = common::build_module(
+        27,
+        &[
+            FixtureFile {
+                name: "sound/raw.bank",
+                group: "snd!",
+                payload,
+                storage: Storage::Raw,
+            },
+            FixtureFile {
+                name: "objects/rifle.bitmap",
+                group: "bitm",
+                payload: b"compressed payload",
+                storage: Storage::Compressed,
+            },
+        ],
+    );
+    let mut reader = Cursor::new(bytes);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+
+    assert_eq!(module.files[0].flags, FileFlags::RAW_FILE);
+    assert_eq!(module.extract_tag(0, &mut reader).unwrap(), payload);
+    assert_eq!(
+        module.extract_tag(1, &mut reader).unwrap(),
+        b"compressed payload"
+    );
+}
+
+#[test]
+fn blockless_entries_without_flags_are_read_verbatim() {
+    let payload = b"stored without compression";
+    let bytes = common::build_module(
+        23,
+        &[FixtureFile {
+            name: "globals/plain.globals",
+            group: "matg",
+            payload,
+            storage: Storage::Uncompressed,
+        }],
+    );
+    let mut reader = Cursor::new(bytes);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+
+    let file = &module.files[0];
+    assert_eq!(file.flags, FileFlags::empty());
+    assert_eq!((file.block_count, file.first_block_index), (0, -1));
+    assert_eq!(module.extract_tag(0, &mut reader).unwrap(), payload);
+}
+
+#[test]
+fn blockless_entries_with_mismatched_sizes_are_rejected() {
+    let bytes = common::build_module(
+        27,
+        &[FixtureFile {
+            name: "globals/plain.globals",
+            group: "matg",
+            payload: b"plain",
+            storage: Storage::Uncompressed,
+        }],
+    );
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.files[0].total_uncompressed_size += 1;
+
+    let error = module.extract_tag(0, &mut reader).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::NonCompressedSingleTag)
+    ));
+}
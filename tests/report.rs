@@ -0,0 +1,25 @@
+use h5_dumper::report::ExtractionReport;
+
+#[test]
+fn merge_adds_counts_and_reasons() {
+    let mut total = ExtractionReport::default();
+    let mut first = ExtractionReport::default();
+    first.write(10, true);
+    first.skip("empty");
+    let mut second = ExtractionReport::default();
+    second.write(5, false);
+    second.skip("empty");
+    second.fail("broken", &anyhow::anyhow!("bad data"));
+    total.merge(first);
+    total.merge(second);
+
+    assert_eq!(total.extracted, 2);
+    assert_eq!(total.bytes_written, 15);
+    assert_eq!((total.compressed, total.raw), (1, 1));
+    assert_eq!(total.skipped, 3);
+    assert_eq!(total.skip_reasons["empty"], 2);
+    assert_eq!(
+        total.errors,
+        [("broken".to_string(), "bad data".to_string())]
+    );
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::{H5Module, ModuleError, TagSection};
+use std::io::Cursor;
+
+#[test]
+fn read_tag_splits_off_resource_blocks() {
+    let payload: Vec<u8> = (0..64).collect();
+    let bytes = common::build_module(
+        27,
+        &[FixtureFile {
+            name: "objects/mesh.render_model",
+            group: "mode",
+            payload: &payload,
+            storage: Storage::Blocks(16),
+        }],
+    );
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.files[0].uncompressed_resource_size = 32;
+    module.files[0].resource_block_count = 2;
+
+    module.read_tag(0, &mut reader).unwrap();
+    assert_eq!(module.files[0].data, payload);
+    assert_eq!(module.files[0].resource_data, payload[32..]);
+}
+
+#[test]
+fn resource_of_unblocked_tag_is_its_tail() {
+    let payload = b"header and tag, then resource".to_vec();
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.files[0].uncompressed_resource_size = 8;
+
+    assert_eq!(module.resource_range(0).unwrap(), 21..29);
+    module.read_tag(0, &mut reader).unwrap();
+    assert_eq!(module.files[0].resource_data, b"resource");
+}
+
+#[test]
+fn oversized_resource_section_is_an_error() {
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", b"tiny");
+    let mut module = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap();
+    module.files[0].uncompressed_resource_size = 5;
+
+    let error = module.resource_range(0).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::SectionOutOfBounds { .. })
+    ));
+}
+
+#[test]
+fn section_ranges_follow_block_counts() {
+    let payload: Vec<u8> = (0..64).collect();
+    let bytes = common::build_module(
+        27,
+        &[FixtureFile {
+            name: "objects/mesh.render_model",
+            group: "mode",
+            payload: &payload,
+            storage: Storage::Blocks(16),
+        }],
+    );
+    let mut module = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap();
+    let file = &mut module.files[0];
+    (file.uncompressed_header_size, file.header_block_count) = (10, 1);
+    (file.uncompressed_tag_size, file.tag_block_count) = (30, 2);
+    (file.uncompressed_resource_size, file.resource_block_count) = (16, 1);
+
+    let ranges: Vec<_> = TagSection::ALL
+        .iter()
+        .map(|&section| module.section_range(0, section).unwrap())
+        .collect();
+    assert_eq!(ranges, [0..10, 16..46, 48..64]);
+}
+
+#[test]
+fn read_tag_keeps_data_when_sections_do_not_fit() {
+    let payload = b"tiny".to_vec();
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.files[0].uncompressed_resource_size = 5;
+
+    module.read_tag(0, &mut reader).unwrap();
+    assert_eq!(module.files[0].data, payload);
+    assert!(module.files[0].resource_data.is_empty());
+}
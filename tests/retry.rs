@@ -0,0 +1,73 @@
+use h5_dumper::common::{RetryPolicy, RetryingReader};
+use std::io::{self, Read};
+use std::time::Duration;
+
+/// Reader that fails its first `failures` reads with `kind` before reading from `data`.
+struct FlakyReader {
+    data: &'static [u8],
+    failures: u32,
+    kind: io::ErrorKind,
+    attempts: u32,
+}
+
+impl FlakyReader {
+    fn new(failures: u32, kind: io::ErrorKind) -> Self {
+        FlakyReader {
+            data: b"module data",
+            failures,
+            kind,
+            attempts: 0,
+        }
+    }
+}
+
+impl Read for &mut FlakyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.attempts += 1;
+        if self.attempts <= self.failures {
+            return Err(io::Error::new(self.kind, "flaky"));
+        }
+        self.data.read(buf)
+    }
+}
+
+fn policy(retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        retries,
+        backoff: Duration::ZERO,
+    }
+}
+
+#[test]
+fn transient_failures_are_retried_until_a_read_succeeds() {
+    let mut inner = FlakyReader::new(3, io::ErrorKind::TimedOut);
+    let mut data = Vec::new();
+    RetryingReader::new(&mut inner, policy(3))
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data, b"module data");
+    // Three failures, the successful read and the read that reports the end.
+    assert_eq!(inner.attempts, 5);
+}
+
+#[test]
+fn running_out_of_retries_returns_the_last_error() {
+    let mut inner = FlakyReader::new(3, io::ErrorKind::ConnectionReset);
+    let mut buf = [0; 4];
+    let error = RetryingReader::new(&mut inner, policy(2))
+        .read(&mut buf)
+        .unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::ConnectionReset);
+    assert_eq!(inner.attempts, 3);
+}
+
+#[test]
+fn permanent_failures_are_not_retried() {
+    let mut inner = FlakyReader::new(1, io::ErrorKind::InvalidData);
+    let mut buf = [0; 4];
+    let error = RetryingReader::new(&mut inner, policy(5))
+        .read(&mut buf)
+        .unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(inner.attempts, 1);
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "schemars")]
+
+mod common;
+
+use h5_dumper::export::{self, ManifestDocument, ModuleManifest};
+use h5_dumper::manifest::SCHEMA_VERSION;
+use h5_dumper::H5Module;
+use serde_json::Value;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Asserts that every key of `object` is a property of `schema` and every required property is present.
+fn assert_matches_properties(object: &Value, schema: &Value) {
+    let properties = schema["properties"].as_object().unwrap();
+    for key in object.as_object().unwrap().keys() {
+        assert!(properties.contains_key(key), "{key} is not in the schema");
+    }
+    for required in schema["required"].as_array().unwrap() {
+        assert!(object.get(required.as_str().unwrap()).is_some());
+    }
+}
+
+#[test]
+fn manifest_schema_describes_the_manifest_output() {
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", b"payload");
+    let module = H5Module::from_reader(&mut Cursor::new(&bytes)).unwrap();
+    let document = serde_json::to_value(ManifestDocument {
+        schema_version: SCHEMA_VERSION,
+        modules: vec![ModuleManifest::new(Path::new("test.module"), &module)],
+    })
+    .unwrap();
+    let schema = serde_json::to_value(export::manifest_schema()).unwrap();
+    let definitions = &schema["$defs"];
+
+    assert_matches_properties(&document, &schema);
+    let module_manifest = &document["modules"][0];
+    assert_matches_properties(module_manifest, &definitions["ModuleManifest"]);
+    assert_matches_properties(&module_manifest["header"], &definitions["ModuleHeader"]);
+    assert_matches_properties(
+        &module_manifest["files"][0],
+        &definitions["ModuleFileEntry"],
+    );
+    assert_eq!(
+        module_manifest["files"][0]["flags"],
+        serde_json::json!(["COMPRESSED"])
+    );
+}
@@ -0,0 +1,68 @@
+#![cfg(all(feature = "serde", feature = "cli"))]
+
+mod common;
+
+use common::{FixtureFile, Storage};
+use std::path::PathBuf;
+use std::process::Command;
+
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("h5_dumper-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        ScratchDir(path)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Extracts a module whose second entry claims a resource section larger than its data, returning stdout.
+fn extract(scratch: &ScratchDir, argument: &str) -> String {
+    let files = ["objects/fine.bitmap", "objects/broken.bitmap"].map(|name| FixtureFile {
+        name,
+        group: "bitm",
+        payload: b"bitmap data",
+        storage: Storage::Compressed,
+    });
+    let mut bytes = common::build_module(27, &files);
+    // uncompressed_resource_size of the second 88 byte entry, after the 56 byte header.
+    let resource_size = 56 + 88 + 76;
+    bytes[resource_size..resource_size + 4].copy_from_slice(&64u32.to_le_bytes());
+    let module = scratch.0.join("test.module");
+    std::fs::write(&module, bytes).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_h5_dumper"))
+        .arg("--module-path")
+        .arg(&module)
+        .args(["extract", "--merge", argument, "--save-path"])
+        .arg(scratch.0.join("out"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn bad_sections_skip_only_the_resource() {
+    let scratch = ScratchDir::new("sections-resources");
+    let stdout = extract(&scratch, "--write-resources");
+    assert!(stdout.contains("Extracted 2 file(s)"), "{stdout}");
+    assert!(stdout.contains("bad section sizes: 1"), "{stdout}");
+    let broken = scratch.0.join("out/objects/broken.bitmap");
+    assert_eq!(std::fs::read(&broken).unwrap(), b"bitmap data");
+}
+
+#[test]
+fn bad_sections_skip_the_split_entry() {
+    let scratch = ScratchDir::new("sections-split");
+    let stdout = extract(&scratch, "--split-sections");
+    assert!(stdout.contains("Extracted 1 file(s)"), "{stdout}");
+    assert!(stdout.contains("bad section sizes: 1"), "{stdout}");
+    assert!(scratch.0.join("out/objects/fine.bitmap.tag").exists());
+    assert!(!scratch.0.join("out/objects/broken.bitmap.tag").exists());
+}
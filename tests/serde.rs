@@ -0,0 +1,29 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use h5_dumper::{FileFlags, H5Module};
+use std::io::Cursor;
+
+#[test]
+fn module_round_trips_through_json() {
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", b"payload");
+    let module = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+    let json = serde_json::to_string(&module).unwrap();
+    let restored: H5Module = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.header.module_id, module.header.module_id);
+    assert_eq!(restored.data_offset, module.data_offset);
+    assert_eq!(restored.files.len(), 1);
+    assert_eq!(restored.files[0].name, "objects/rifle.bitmap");
+    assert_eq!(restored.files[0].flags, FileFlags::COMPRESSED);
+    assert_eq!(restored.find_by_name("objects/rifle.bitmap"), Some(0));
+}
+
+#[test]
+fn file_flags_serialize_as_names() {
+    let flags = FileFlags::COMPRESSED | FileFlags::HAS_BLOCKS | FileFlags::from_bits_retain(0x10);
+    let json = serde_json::to_string(&flags).unwrap();
+    assert_eq!(json, r#"["COMPRESSED","HAS_BLOCKS","0x10"]"#);
+    assert_eq!(serde_json::from_str::<FileFlags>(&json).unwrap(), flags);
+}
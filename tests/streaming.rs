@@ -0,0 +1,139 @@
+#![cfg(all(feature = "serde", feature = "cli"))]
+
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::H5Module;
+use std::io::{Cursor, Read};
+use std::path::PathBuf;
+use std::process::Command;
+
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("h5_dumper-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        ScratchDir(path)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+const NAMES: [&str; 4] = [
+    "objects/rifle.bitmap",
+    "objects/blocks.model",
+    "sound/raw.sound",
+    "levels/plain.scenario",
+];
+
+fn fixture() -> Vec<u8> {
+    let text = b"bitmap data".repeat(8);
+    let blocks = (0..=255).collect::<Vec<u8>>();
+    let payloads = [&text[..], &blocks[..], &text[..], &blocks[..]];
+    let storages = [
+        Storage::Compressed,
+        Storage::Blocks(48),
+        Storage::Raw,
+        Storage::Uncompressed,
+    ];
+    let files: Vec<_> = NAMES
+        .iter()
+        .zip(payloads)
+        .zip(storages)
+        .map(|((name, payload), storage)| FixtureFile {
+            name,
+            group: "bitm",
+            payload,
+            storage,
+        })
+        .collect();
+    common::build_module(27, &files)
+}
+
+/// Runs `extract --merge` on `bytes` with `arguments`, returning stdout.
+fn extract(scratch: &ScratchDir, bytes: &[u8], arguments: &[&str]) -> String {
+    let module = scratch.0.join("test.module");
+    std::fs::write(&module, bytes).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_h5_dumper"))
+        .arg("--module-path")
+        .arg(&module)
+        .args(["extract", "--merge"])
+        .args(arguments)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn expected_tags(bytes: &[u8]) -> Vec<Vec<u8>> {
+    let mut reader = Cursor::new(bytes);
+    let module = H5Module::from_reader(&mut reader).unwrap();
+    (0..module.files.len() as u32)
+        .map(|index| module.extract_tag(index, &mut reader).unwrap())
+        .collect()
+}
+
+#[test]
+fn streamed_files_match_extract_tag() {
+    let scratch = ScratchDir::new("streaming-directory");
+    let bytes = fixture();
+    let out = scratch.0.join("out");
+    let stdout = extract(&scratch, &bytes, &["--save-path", out.to_str().unwrap()]);
+    assert!(stdout.contains("Extracted 4 file(s)"), "{stdout}");
+    for (name, expected) in NAMES.iter().zip(expected_tags(&bytes)) {
+        assert_eq!(std::fs::read(out.join(name)).unwrap(), expected, "{name}");
+    }
+}
+
+#[test]
+fn streamed_archive_entries_match_extract_tag() {
+    let scratch = ScratchDir::new("streaming-archive");
+    let bytes = fixture();
+    let archive_path = scratch.0.join("out.zip");
+    extract(
+        &scratch,
+        &bytes,
+        &["--output-archive", archive_path.to_str().unwrap()],
+    );
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&archive_path).unwrap()).unwrap();
+    for (name, expected) in NAMES.iter().zip(expected_tags(&bytes)) {
+        let mut data = Vec::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data, expected, "{name}");
+    }
+}
+
+#[test]
+fn skipped_stream_leaves_no_file_and_no_limit_claim() {
+    let scratch = ScratchDir::new("streaming-skip");
+    let mut bytes = fixture();
+    // total_compressed_size of the uncompressed fourth entry, which then no longer matches its size.
+    let compressed_size = 56 + 3 * 88 + 32;
+    bytes[compressed_size..compressed_size + 4].copy_from_slice(&255u32.to_le_bytes());
+    let out = scratch.0.join("out");
+    let stdout = extract(
+        &scratch,
+        &bytes,
+        &[
+            "--sort",
+            "name",
+            "--limit",
+            "1",
+            "--save-path",
+            out.to_str().unwrap(),
+        ],
+    );
+    assert!(stdout.contains("Extracted 1 file(s)"), "{stdout}");
+    assert!(!out.join(NAMES[3]).exists());
+    // levels/plain.scenario sorts first, so its failed claim must not use up the limit.
+    assert!(out.join(NAMES[1]).exists());
+}
@@ -0,0 +1,75 @@
+use h5_dumper::BufReaderExt;
+use proptest::prelude::*;
+use std::io::{BufReader, Cursor};
+
+/// Arbitrary strings, half of them with NULs mixed in anywhere, from empty up to a few hundred characters.
+fn strings() -> impl Strategy<Value = String> {
+    let with_nulls =
+        prop::collection::vec(prop_oneof![3 => any::<char>(), 1 => Just('\0')], 0..300)
+            .prop_map(String::from_iter);
+    prop_oneof![".{0,300}", with_nulls]
+}
+
+proptest! {
+    #[test]
+    fn cstrings_round_trip_up_to_the_first_null(string in strings(), capacity in 1usize..16) {
+        let mut bytes = string.as_bytes().to_vec();
+        bytes.push(0);
+        bytes.extend_from_slice(b"next\0");
+        let expected = string.split('\0').next().unwrap();
+
+        let mut cursor = Cursor::new(&bytes);
+        prop_assert_eq!(cursor.read_cstring().unwrap(), expected);
+        // A small buffer makes strings span several fills.
+        let mut reader = BufReader::with_capacity(capacity, bytes.as_slice());
+        prop_assert_eq!(reader.read_cstring().unwrap(), expected);
+        if !string.contains('\0') {
+            prop_assert_eq!(cursor.read_cstring().unwrap(), "next");
+            prop_assert_eq!(reader.read_cstring().unwrap(), "next");
+        }
+    }
+
+    #[test]
+    fn fixed_strings_round_trip_without_trailing_nulls(
+        string in strings(),
+        padding in 0usize..8,
+        capacity in 1usize..16,
+    ) {
+        let mut bytes = string.as_bytes().to_vec();
+        let length = bytes.len() + padding;
+        bytes.resize(length, 0);
+        bytes.extend_from_slice(b"next");
+        let expected = string.trim_end_matches('\0');
+
+        let mut cursor = Cursor::new(&bytes);
+        prop_assert_eq!(cursor.read_fixed_string(length).unwrap(), expected);
+        prop_assert_eq!(cursor.read_fixed_string(4).unwrap(), "next");
+        let mut reader = BufReader::with_capacity(capacity, bytes.as_slice());
+        prop_assert_eq!(reader.read_fixed_string(length).unwrap(), expected);
+    }
+}
+
+#[test]
+fn unterminated_cstring_reads_to_the_end() {
+    let mut cursor = Cursor::new(b"unterminated");
+    assert_eq!(cursor.read_cstring().unwrap(), "unterminated");
+    assert_eq!(cursor.read_cstring().unwrap(), "");
+}
+
+#[test]
+fn short_fixed_string_is_an_error() {
+    let mut cursor = Cursor::new(b"short");
+    assert!(cursor.read_fixed_string(6).is_err());
+}
+
+#[test]
+fn invalid_utf8_is_replaced_by_both_readers() {
+    assert_eq!(
+        Cursor::new(b"bad\xff\0").read_cstring().unwrap(),
+        "bad\u{fffd}"
+    );
+    assert_eq!(
+        Cursor::new(b"bad\xff\0\0").read_fixed_string(6).unwrap(),
+        "bad\u{fffd}"
+    );
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use h5_dumper::{H5Module, ModuleError, ModuleVersion, Title};
+use std::io::Cursor;
+
+#[test]
+fn known_versions_map_to_layout() {
+    let campaign = ModuleVersion::try_from(23).unwrap();
+    assert_eq!(campaign, ModuleVersion::H5Campaign);
+    assert!(!campaign.has_checksums());
+    assert_eq!(campaign.header_size(), 48);
+    assert_eq!(campaign.block_size(), 20);
+
+    let forge = ModuleVersion::try_from(27).unwrap();
+    assert_eq!(forge, ModuleVersion::H5Forge);
+    assert!(forge.has_checksums());
+    assert_eq!(forge.header_size(), 56);
+    assert_eq!(forge.block_size(), 32);
+}
+
+#[test]
+fn unknown_version_is_rejected() {
+    assert!(matches!(
+        ModuleVersion::try_from(25),
+        Err(ModuleError::InvalidModuleVersion(25))
+    ));
+
+    let mut bytes = common::single_file_module(27, "a", "bitm", b"data");
+    bytes[4..8].copy_from_slice(&25u32.to_le_bytes());
+    let error = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::InvalidModuleVersion(25))
+    ));
+}
+
+#[test]
+fn title_follows_module_version() {
+    for (version, title) in [(23, Title::Campaign), (27, Title::Forge)] {
+        let bytes = common::single_file_module(version, "a", "bitm", b"data");
+        let module = H5Module::from_reader(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(module.title(), Some(title));
+    }
+    assert_eq!(H5Module::default().title(), None);
+    assert_eq!(Title::Forge.to_string(), "Halo 5 Forge");
+}
+
+#[test]
+fn unread_header_has_no_version() {
+    let module = H5Module::default();
+    assert!(matches!(
+        module.header.version_kind(),
+        Err(ModuleError::InvalidModuleVersion(0))
+    ));
+    assert!(module.header.size().is_err());
+}
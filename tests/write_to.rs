@@ -0,0 +1,88 @@
+mod common;
+
+use common::{FixtureFile, Storage};
+use h5_dumper::{H5Module, ModuleError};
+use std::io::Cursor;
+
+#[test]
+fn written_tags_match_extracted_tags() {
+    let text = b"bitmap data".repeat(8);
+    let blocks = (0..=255).collect::<Vec<u8>>();
+    let files = [
+        ("compressed", Storage::Compressed, &text[..]),
+        ("blocks", Storage::Blocks(48), &blocks[..]),
+        ("raw", Storage::Raw, &text[..]),
+        ("uncompressed", Storage::Uncompressed, &blocks[..]),
+    ]
+    .map(|(name, storage, payload)| FixtureFile {
+        name,
+        group: "bitm",
+        payload,
+        storage,
+    });
+    let mut reader = Cursor::new(common::build_module(27, &files));
+    let module = H5Module::from_reader(&mut reader).unwrap();
+
+    for (index, file) in files.iter().enumerate() {
+        let mut out = Vec::new();
+        let written = module
+            .write_tag_to(index as u32, &mut reader, &mut out)
+            .unwrap();
+        assert_eq!(written, out.len() as u64);
+        assert_eq!(out, file.payload, "{}", file.name);
+        assert_eq!(out, module.extract_tag(index as u32, &mut reader).unwrap());
+    }
+}
+
+#[test]
+fn blocks_listed_out_of_order_are_written_in_offset_order() {
+    let payload: Vec<u8> = (0..64).collect();
+    let files = [FixtureFile {
+        name: "objects/blocks.model",
+        group: "mode",
+        payload: &payload,
+        storage: Storage::Blocks(16),
+    }];
+    let mut reader = Cursor::new(common::build_module(27, &files));
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.blocks.reverse();
+
+    let mut out = Vec::new();
+    let written = module.write_tag_to(0, &mut reader, &mut out).unwrap();
+    assert_eq!(written, 64);
+    assert_eq!(out, payload);
+}
+
+#[test]
+fn short_stream_is_an_error() {
+    let payload = b"bitmap data".repeat(8);
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.files[0].total_uncompressed_size += 1;
+
+    let mut out = Vec::new();
+    let error = module.write_tag_to(0, &mut reader, &mut out).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::DecompressedSizeMismatch { actual, .. }) if *actual == payload.len() as u64
+    ));
+    assert_eq!(out, payload);
+}
+
+#[test]
+fn long_stream_is_cut_at_the_declared_size() {
+    let payload = b"bitmap data".repeat(8);
+    let bytes = common::single_file_module(27, "objects/rifle.bitmap", "bitm", &payload);
+    let mut reader = Cursor::new(bytes);
+    let mut module = H5Module::from_reader(&mut reader).unwrap();
+    module.files[0].total_uncompressed_size -= 1;
+
+    let mut out = Vec::new();
+    let error = module.write_tag_to(0, &mut reader, &mut out).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(ModuleError::DecompressedSizeMismatch { .. })
+    ));
+    assert_eq!(out, payload[..payload.len() - 1]);
+}